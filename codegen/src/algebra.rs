@@ -1,5 +1,11 @@
+use crate::{dsl::ParseError, metric::ChangeOfBasis};
+
 pub struct GeometricAlgebra<'a> {
     pub generator_squares: &'a [isize],
+    /// Empty if the generators are named by their hexadecimal index (`e0` to `eF`)
+    pub generator_names: &'a [String],
+    /// Off-diagonal entries `(i, j, value)` with `i < j` of the symmetric metric, empty for an orthogonal basis
+    pub generator_inner_products: &'a [(usize, usize, isize)],
 }
 
 impl<'a> GeometricAlgebra<'a> {
@@ -7,15 +13,33 @@ impl<'a> GeometricAlgebra<'a> {
         1 << self.generator_squares.len()
     }
 
+    /// Index of the pseudoscalar, which contains all generators
+    pub fn pseudoscalar_index(&self) -> BasisElementIndex {
+        BasisElementIndex::MAX
+            .checked_shr((BasisElementIndex::BITS as usize - self.generator_squares.len()) as u32)
+            .unwrap_or(0)
+    }
+
+    /// Basis element of the given index, oriented like its complement if that comes first in the sorted basis
+    pub fn basis_element(&self, index: BasisElementIndex) -> BasisElement {
+        let mut element = BasisElement::from_index(index);
+        let complement = element.right_complement(self);
+        if complement.cmp(&element) == std::cmp::Ordering::Less {
+            element.scalar = complement.scalar;
+        }
+        element
+    }
+
+    /// All basis elements, so only feasible for algebras with few generators
     pub fn basis(&self) -> impl Iterator<Item = BasisElement> + '_ {
-        (0..self.basis_size() as BasisElementIndex).map(move |index| {
-            let mut element = BasisElement::from_index(index);
-            let dual = element.dual(self);
-            if dual.cmp(&element) == std::cmp::Ordering::Less {
-                element.scalar = element.dual(self).scalar;
-            }
-            element
-        })
+        (0..self.basis_size() as BasisElementIndex).map(move |index| self.basis_element(index))
+    }
+
+    /// The given basis elements in the order of `sorted_basis`
+    pub fn sorted_elements(&self, indices: &[BasisElementIndex]) -> Vec<BasisElement> {
+        let mut basis_elements = indices.iter().map(|index| self.basis_element(*index)).collect::<Vec<BasisElement>>();
+        basis_elements.sort();
+        basis_elements
     }
 
     pub fn sorted_basis(&self) -> Vec<BasisElement> {
@@ -23,9 +47,86 @@ impl<'a> GeometricAlgebra<'a> {
         basis_elements.sort();
         basis_elements
     }
+
+    /// Symmetric matrix of the inner products of the generators
+    pub fn metric(&self) -> Vec<Vec<isize>> {
+        let mut metric = (0..self.generator_squares.len())
+            .map(|i| {
+                (0..self.generator_squares.len())
+                    .map(|j| if i == j { self.generator_squares[i] } else { 0 })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        for (i, j, value) in self.generator_inner_products.iter() {
+            metric[*i][*j] = *value;
+            metric[*j][*i] = *value;
+        }
+        metric
+    }
+
+    /// Whether a generator is orthogonal to all generators including itself, as `e0` in projective geometric algebras
+    pub fn is_degenerate(&self) -> bool {
+        self.metric().iter().any(|row| row.iter().all(|value| *value == 0))
+    }
+
+    /// Outer product of the metric applied to each generator of the basis element
+    ///
+    /// Degenerate generators are mapped to a zero multiple of themselves, so that the image keeps its grade.
+    /// `None` if a generator is mapped to a sum of generators, which happens when its metric row has more than one entry.
+    pub fn metric_image(&self, element: &BasisElement) -> Option<BasisElement> {
+        let metric = self.metric();
+        element.component_bits().try_fold(
+            BasisElement {
+                scalar: element.scalar,
+                index: 0,
+            },
+            |image, generator| {
+                let mut entries = metric[generator].iter().enumerate().filter(|(_j, value)| **value != 0);
+                let generator_image = match (entries.next(), entries.next()) {
+                    (None, _) => BasisElement {
+                        scalar: 0,
+                        index: 1 << generator,
+                    },
+                    (Some((j, value)), None) => BasisElement {
+                        scalar: *value,
+                        index: 1 << j,
+                    },
+                    _ => return None,
+                };
+                // The metric is symmetric, so two generators never map to the same one
+                Some(BasisElement::product(&image, &generator_image, self))
+            },
+        )
+    }
+
+    /// Geometric product of two basis elements
+    ///
+    /// Basis elements are outer products of the generators, so with a non-diagonal metric the result can be a sum of basis elements.
+    pub fn product(&self, a: &BasisElement, b: &BasisElement) -> Vec<BasisElement> {
+        if self.generator_inner_products.is_empty() {
+            let product = BasisElement::product(a, b, self);
+            if product.scalar == 0 {
+                vec![]
+            } else {
+                vec![product]
+            }
+        } else {
+            ChangeOfBasis::new(self).product(a, b)
+        }
+    }
 }
 
-pub type BasisElementIndex = u16;
+/// Bit set of the generators of a basis element, which limits algebras to 64 generators
+pub type BasisElementIndex = u64;
+
+/// Single character generator names are concatenated, longer ones are separated by underscores
+pub fn generator_name_separator(generator_names: &[String]) -> &'static str {
+    if generator_names.iter().all(|name| name.chars().count() == 1) {
+        ""
+    } else {
+        "_"
+    }
+}
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct BasisElement {
@@ -38,23 +139,131 @@ impl BasisElement {
         Self { scalar: 1, index }
     }
 
-    pub fn parse(mut name: &str, algebra: &GeometricAlgebra) -> Self {
+    pub fn parse(name: &str, algebra: &GeometricAlgebra) -> Result<Self, ParseError> {
         let mut result = Self::from_index(0);
-        if name.starts_with('-') {
-            name = &name[1..];
+        let (sign_length, unsigned_name) = if let Some(unsigned_name) = name.strip_prefix('-') {
             result.scalar = -1;
+            (1, unsigned_name)
+        } else {
+            (0, name)
+        };
+        if unsigned_name == "1" {
+            return Ok(result);
+        }
+        match Self::parse_generator_names(unsigned_name, algebra.generator_names) {
+            Ok(generator_indices) => {
+                for (i, generator_index) in generator_indices.iter().enumerate() {
+                    if generator_indices[0..i].contains(generator_index) {
+                        return Err(ParseError::new(
+                            sign_length,
+                            format!("generator {} occurs more than once", algebra.generator_names[*generator_index]),
+                        ));
+                    }
+                    result = BasisElement::product(&result, &Self::from_index(1 << generator_index), algebra);
+                }
+                return Ok(result);
+            }
+            // Single hexadecimal digits only index 16 generators, so `e01` would be ambiguous in larger algebras
+            Err((column, part)) if algebra.generator_squares.len() > 16 => {
+                let generator_names = algebra.generator_names;
+                let separator = generator_name_separator(generator_names);
+                let is_indexed = generator_names.iter().enumerate().all(|(index, name)| *name == format!("e{}", index));
+                let generators = if is_indexed {
+                    format!("{} to {}", generator_names[0], generator_names[generator_names.len() - 1])
+                } else {
+                    generator_names.join(", ")
+                };
+                return Err(
+                    ParseError::new(sign_length + column, format!("`{}` is not a generator", part)).with_suggestion(format!(
+                        "join the generators {} with `{}`, e.g. `{}{}{}`",
+                        generators, separator, generator_names[0], separator, generator_names[1]
+                    )),
+                );
+            }
+            Err(_) => {}
         }
-        if name == "1" {
-            return result;
+        let mut generator_indices = unsigned_name.char_indices().map(|(column, c)| (column + sign_length, c)).peekable();
+        match generator_indices.next() {
+            Some((_, 'e')) if generator_indices.peek().is_some() => {}
+            _ => {
+                return Err(ParseError::new(0, format!("expected basis element, found `{}`", name)).with_suggestion(
+                    if algebra.generator_names.is_empty() {
+                        "write `1` for the scalar or `e` followed by generator indices, e.g. `e012`".to_string()
+                    } else {
+                        format!(
+                            "write `1` for the scalar, `e` followed by generator indices or a product of the generators {}",
+                            algebra.generator_names.join(", ")
+                        )
+                    },
+                ))
+            }
         }
-        let mut generator_indices = name.chars();
-        assert_eq!(generator_indices.next().unwrap(), 'e');
-        for generator_index in generator_indices {
-            let generator_index = generator_index.to_digit(16).unwrap();
-            assert!((generator_index as usize) < algebra.generator_squares.len());
+        let mut used_generators: BasisElementIndex = 0;
+        for (column, generator_index) in generator_indices {
+            let generator_index = generator_index.to_digit(16).ok_or_else(|| {
+                ParseError::new(column, format!("`{}` is not a generator index", generator_index))
+                    .with_suggestion("use hexadecimal digits".to_string())
+            })?;
+            if generator_index as usize >= algebra.generator_squares.len() {
+                return Err(ParseError::new(
+                    column,
+                    format!(
+                        "generator {} out of range for {}-generator algebra",
+                        generator_index,
+                        algebra.generator_squares.len()
+                    ),
+                )
+                .with_suggestion(format!("use e0 to e{:X}", algebra.generator_squares.len() - 1)));
+            }
+            if used_generators & (1 << generator_index) != 0 {
+                return Err(ParseError::new(column, format!("generator {} occurs more than once", generator_index)));
+            }
+            used_generators |= 1 << generator_index;
             result = BasisElement::product(&result, &Self::from_index(1 << generator_index), algebra);
         }
-        result
+        Ok(result)
+    }
+
+    /// Splits a name like `xy` or `no_ni` into generator indices, if it only consists of user-defined generator names
+    ///
+    /// Otherwise returns the column and the first part which is not a generator name.
+    fn parse_generator_names<'a>(name: &'a str, generator_names: &[String]) -> Result<Vec<usize>, (usize, &'a str)> {
+        let find = |(column, part): (usize, &'a str)| {
+            generator_names
+                .iter()
+                .position(|generator_name| generator_name == part)
+                .ok_or((column, part))
+        };
+        if generator_names.is_empty() {
+            Err((0, name))
+        } else if generator_name_separator(generator_names).is_empty() {
+            name.char_indices()
+                .map(|(column, c)| find((column, &name[column..column + c.len_utf8()])))
+                .collect()
+        } else {
+            let mut column = 0;
+            name.split('_')
+                .map(|part| {
+                    let part_column = column;
+                    column += part.len() + 1;
+                    find((part_column, part))
+                })
+                .collect()
+        }
+    }
+
+    /// Name without the sign, e.g. `e012`, or `xy` and `no_ni` for user-defined generator names
+    pub fn name(&self, generator_names: &[String]) -> String {
+        if self.index == 0 {
+            "1".to_string()
+        } else if generator_names.is_empty() {
+            format!("e{}", self.component_bits().map(|index| format!("{:X}", index)).collect::<String>())
+        } else {
+            self.component_bits()
+                .map(|index| generator_names[index].as_str())
+                .collect::<Vec<_>>()
+                .join(generator_name_separator(generator_names))
+        }
     }
 
     pub fn grade(&self) -> usize {
@@ -65,18 +274,29 @@ impl BasisElement {
         (0..std::mem::size_of::<BasisElementIndex>() * 8).filter(move |index| (self.index >> index) & 1 != 0)
     }
 
-    pub fn dual(&self, algebra: &GeometricAlgebra) -> Self {
+    /// Complement of the index, signed such that the outer product `self ∧ self.right_complement()` is the pseudoscalar
+    pub fn right_complement(&self, algebra: &GeometricAlgebra) -> Self {
         let mut result = Self {
             scalar: self.scalar,
-            index: algebra.basis_size() as BasisElementIndex - 1 - self.index,
+            index: algebra.pseudoscalar_index() ^ self.index,
         };
         result.scalar *= BasisElement::product(self, &result, algebra).scalar;
         result
     }
 
+    /// Complement of the index, signed such that the outer product `self.left_complement() ∧ self` is the pseudoscalar
+    pub fn left_complement(&self, algebra: &GeometricAlgebra) -> Self {
+        let mut result = Self {
+            scalar: self.scalar,
+            index: algebra.pseudoscalar_index() ^ self.index,
+        };
+        result.scalar *= BasisElement::product(&result, self, algebra).scalar;
+        result
+    }
+
     pub fn product(a: &Self, b: &Self, algebra: &GeometricAlgebra) -> Self {
         let commutations = a.component_bits().fold((0, a.index, b.index), |(commutations, a, b), index| {
-            let hurdles_a = a & (BasisElementIndex::MAX << (index + 1));
+            let hurdles_a = a & BasisElementIndex::MAX.checked_shl(index as u32 + 1).unwrap_or(0);
             let hurdles_b = b & ((1 << index) - 1);
             (
                 commutations + Self::from_index(hurdles_a | hurdles_b).grade(),
@@ -96,18 +316,8 @@ impl BasisElement {
 
 impl std::fmt::Display for BasisElement {
     fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let name = format!("e{}", self.component_bits().map(|index| format!("{:X}", index)).collect::<String>());
-        formatter.pad_integral(
-            self.scalar >= 0,
-            "",
-            if self.scalar == 0 {
-                "0"
-            } else if self.index == 0 {
-                "1"
-            } else {
-                name.as_str()
-            },
-        )
+        let name = self.name(&[]);
+        formatter.pad_integral(self.scalar >= 0, "", if self.scalar == 0 { "0" } else { name.as_str() })
     }
 }
 
@@ -133,15 +343,32 @@ impl std::cmp::PartialOrd for BasisElement {
     }
 }
 
+/// Names of traits are `&'static str` throughout the AST, so names built at runtime are leaked, each one only once
+pub fn static_name(name: String) -> &'static str {
+    static NAMES: std::sync::Mutex<std::collections::BTreeSet<&'static str>> = std::sync::Mutex::new(std::collections::BTreeSet::new());
+    let mut names = NAMES.lock().unwrap();
+    if let Some(static_name) = names.get(name.as_str()) {
+        return static_name;
+    }
+    let static_name: &'static str = Box::leak(name.into_boxed_str());
+    names.insert(static_name);
+    static_name
+}
+
+/// Name of the trait projecting onto the elements of the given grade, e.g. `Grade2`
+pub fn grade_projection_name(grade: usize) -> &'static str {
+    static_name(format!("Grade{}", grade))
+}
+
 #[derive(Clone)]
 pub struct Involution {
     pub terms: Vec<(BasisElement, BasisElement)>,
 }
 
 impl Involution {
-    pub fn identity(algebra: &GeometricAlgebra) -> Self {
+    pub fn identity(basis: &[BasisElement]) -> Self {
         Self {
-            terms: algebra.basis().map(|element| (element.clone(), element)).collect(),
+            terms: basis.iter().map(|element| (element.clone(), element.clone())).collect(),
         }
     }
 
@@ -151,6 +378,16 @@ impl Involution {
         }
     }
 
+    /// Keeps only the terms of the basis elements whose grade is selected
+    pub fn selected<F>(&self, grade_selection: F) -> Self
+    where
+        F: Fn(usize) -> bool,
+    {
+        Self {
+            terms: self.terms.iter().filter(|(key, _value)| grade_selection(key.grade())).cloned().collect(),
+        }
+    }
+
     pub fn negated<F>(&self, grade_negation: F) -> Self
     where
         F: Fn(usize) -> bool,
@@ -168,21 +405,64 @@ impl Involution {
         }
     }
 
-    pub fn dual(&self, algebra: &GeometricAlgebra) -> Self {
+    pub fn right_complement(&self, algebra: &GeometricAlgebra) -> Self {
         Self {
-            terms: self.terms.iter().map(|(key, value)| (key.clone(), value.dual(algebra))).collect(),
+            terms: self
+                .terms
+                .iter()
+                .map(|(key, value)| (key.clone(), value.right_complement(algebra)))
+                .collect(),
         }
     }
 
-    pub fn involutions(algebra: &GeometricAlgebra) -> Vec<(&'static str, Self)> {
-        let involution = Self::identity(algebra);
-        vec![
+    pub fn left_complement(&self, algebra: &GeometricAlgebra) -> Self {
+        Self {
+            terms: self
+                .terms
+                .iter()
+                .map(|(key, value)| (key.clone(), value.left_complement(algebra)))
+                .collect(),
+        }
+    }
+
+    /// Right complement of the metric image, `None` if the metric is not a signed permutation of the generators
+    pub fn hodge_dual(&self, algebra: &GeometricAlgebra) -> Option<Self> {
+        let terms = self
+            .terms
+            .iter()
+            .map(|(key, value)| Some((key.clone(), algebra.metric_image(value)?.right_complement(algebra))))
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self { terms })
+    }
+
+    /// Metric image of the left complement, `None` if the metric is not a signed permutation of the generators
+    pub fn inverse_hodge_dual(&self, algebra: &GeometricAlgebra) -> Option<Self> {
+        let terms = self
+            .terms
+            .iter()
+            .map(|(key, value)| Some((key.clone(), algebra.metric_image(&value.left_complement(algebra))?)))
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self { terms })
+    }
+
+    /// The involutions restricted to the given basis elements
+    pub fn involutions(basis: &[BasisElement], algebra: &GeometricAlgebra) -> Vec<(&'static str, Self)> {
+        let involution = Self::identity(basis);
+        let dimension = algebra.generator_squares.len();
+        let mut involutions = vec![
             ("Neg", involution.negated(|_grade| true)),
             ("Automorphism", involution.negated(|grade| grade % 2 == 1)),
             ("Reversal", involution.negated(|grade| grade % 4 >= 2)),
             ("Conjugation", involution.negated(|grade| (grade + 3) % 4 < 2)),
-            ("Dual", involution.dual(algebra)),
-        ]
+            ("AntiReversal", involution.negated(|grade| (dimension - grade) % 4 >= 2)),
+            ("RightComplement", involution.right_complement(algebra)),
+            ("LeftComplement", involution.left_complement(algebra)),
+        ];
+        if let (Some(hodge_dual), Some(inverse_hodge_dual)) = (involution.hodge_dual(algebra), involution.inverse_hodge_dual(algebra)) {
+            involutions.push(("HodgeDual", hodge_dual));
+            involutions.push(("InverseHodgeDual", inverse_hodge_dual));
+        }
+        involutions
     }
 }
 
@@ -200,19 +480,27 @@ pub struct Product {
 
 impl Product {
     pub fn new(a: &[BasisElement], b: &[BasisElement], algebra: &GeometricAlgebra) -> Self {
-        Self {
-            terms: a
-                .iter()
-                .flat_map(|a| {
-                    b.iter().map(move |b| ProductTerm {
-                        product: BasisElement::product(a, b, algebra),
-                        factor_a: a.clone(),
-                        factor_b: b.clone(),
-                    })
-                })
-                .filter(|term| term.product.scalar != 0)
-                .collect(),
+        // Diagonal metrics are the common case, where the product of two basis elements is a single basis element
+        let change_of_basis = if algebra.generator_inner_products.is_empty() {
+            None
+        } else {
+            Some(ChangeOfBasis::new(algebra))
+        };
+        let mut terms = Vec::new();
+        for a in a.iter() {
+            for b in b.iter() {
+                let products = match &change_of_basis {
+                    Some(change_of_basis) => change_of_basis.product(a, b),
+                    None => vec![BasisElement::product(a, b, algebra)],
+                };
+                terms.extend(products.into_iter().filter(|product| product.scalar != 0).map(|product| ProductTerm {
+                    product,
+                    factor_a: a.clone(),
+                    factor_b: b.clone(),
+                }));
+            }
         }
+        Self { terms }
     }
 
     pub fn projected<F>(&self, grade_projection: F) -> Self
@@ -229,31 +517,127 @@ impl Product {
         }
     }
 
-    pub fn dual(&self, algebra: &GeometricAlgebra) -> Self {
+    /// `(a * b + sign * b * a) / 2` where `reversed` is the product of the factors in swapped order
+    pub fn symmetrized(&self, reversed: &Self, sign: isize) -> Self {
+        let mut terms = self.terms.clone();
+        let mut term_positions = terms
+            .iter()
+            .enumerate()
+            .map(|(position, term)| ((term.factor_a.index, term.factor_b.index, term.product.index), position))
+            .collect::<std::collections::HashMap<_, _>>();
+        for term in reversed.terms.iter() {
+            let key = (term.factor_b.index, term.factor_a.index, term.product.index);
+            if let Some(position) = term_positions.get(&key) {
+                terms[*position].product.scalar += sign * term.product.scalar;
+            } else {
+                term_positions.insert(key, terms.len());
+                terms.push(ProductTerm {
+                    product: BasisElement {
+                        scalar: sign * term.product.scalar,
+                        index: term.product.index,
+                    },
+                    factor_a: term.factor_b.clone(),
+                    factor_b: term.factor_a.clone(),
+                });
+            }
+        }
+        terms.retain(|term| term.product.scalar != 0);
+        for term in terms.iter_mut() {
+            assert_eq!(term.product.scalar % 2, 0, "non-integer coefficient in symmetrized product");
+            term.product.scalar /= 2;
+        }
+        Self { terms }
+    }
+
+    pub fn negated<F>(&self, term_negation: F) -> Self
+    where
+        F: Fn(usize, usize, usize) -> bool,
+    {
+        Self {
+            terms: self
+                .terms
+                .iter()
+                .map(|term| {
+                    let mut term = term.clone();
+                    term.product.scalar *= if term_negation(term.factor_a.grade(), term.factor_b.grade(), term.product.grade()) {
+                        -1
+                    } else {
+                        1
+                    };
+                    term
+                })
+                .collect(),
+        }
+    }
+
+    /// Left complement of the product and the factors, which undoes taking the right complements of the factors
+    pub fn left_complement(&self, algebra: &GeometricAlgebra) -> Self {
         Self {
             terms: self
                 .terms
                 .iter()
                 .map(|term| ProductTerm {
-                    product: term.product.dual(algebra),
-                    factor_a: term.factor_a.dual(algebra),
-                    factor_b: term.factor_b.dual(algebra),
+                    product: term.product.left_complement(algebra),
+                    factor_a: term.factor_a.left_complement(algebra),
+                    factor_b: term.factor_b.left_complement(algebra),
                 })
                 .collect(),
         }
     }
 
-    pub fn products(algebra: &GeometricAlgebra) -> Vec<(&'static str, Self)> {
-        let basis = algebra.basis().collect::<Vec<_>>();
-        let product = Self::new(&basis, &basis, algebra);
+    /// Whether a * involution(a) is a scalar for every a, where `self` is the product of a class with itself
+    ///
+    /// The terms of the factors i, j and j, i are summed up, as they multiply the same coefficients of a.
+    pub fn squares_to_scalar(&self, involution: &Involution) -> bool {
+        let sign = |element: &BasisElement| {
+            involution
+                .terms
+                .iter()
+                .find(|(key, _value)| key.index == element.index)
+                .map(|(key, value)| key.scalar * value.scalar)
+                .unwrap_or(0)
+        };
+        let mut coefficients = std::collections::HashMap::new();
+        for term in self.terms.iter().filter(|term| term.product.index != 0) {
+            let factors = if term.factor_a.index < term.factor_b.index {
+                (term.factor_a.index, term.factor_b.index)
+            } else {
+                (term.factor_b.index, term.factor_a.index)
+            };
+            *coefficients.entry((factors, term.product.index)).or_insert(0) += term.product.scalar * sign(&term.factor_b);
+        }
+        coefficients.values().all(|coefficient| *coefficient == 0)
+    }
+
+    /// The products restricted to factors from `a` and `b`
+    ///
+    /// Only the terms of these factors are computed, so that algebras with many generators do not need the whole table.
+    pub fn products(a: &[BasisElement], b: &[BasisElement], algebra: &GeometricAlgebra) -> Vec<(&'static str, Self)> {
+        let product = Self::new(a, b, algebra);
+        let reversed_product = Self::new(b, a, algebra);
+        // The anti products are the left complements of the products of the right complements
+        let complement_a = a.iter().map(|element| element.right_complement(algebra)).collect::<Vec<_>>();
+        let complement_b = b.iter().map(|element| element.right_complement(algebra)).collect::<Vec<_>>();
+        let anti_product = Self::new(&complement_a, &complement_b, algebra);
         vec![
             ("GeometricProduct", product.clone()),
-            ("RegressiveProduct", product.projected(|r, s, t| t == r + s).dual(algebra)),
+            ("RegressiveProduct", anti_product.projected(|r, s, t| t == r + s).left_complement(algebra)),
             ("OuterProduct", product.projected(|r, s, t| t == r + s)),
             ("InnerProduct", product.projected(|r, s, t| t == (r as isize - s as isize).unsigned_abs())),
             ("LeftContraction", product.projected(|r, s, t| t as isize == s as isize - r as isize)),
             ("RightContraction", product.projected(|r, s, t| t as isize == r as isize - s as isize)),
             ("ScalarProduct", product.projected(|_r, _s, t| t == 0)),
+            ("CommutatorProduct", product.symmetrized(&reversed_product, -1)),
+            ("AnticommutatorProduct", product.symmetrized(&reversed_product, 1)),
+            ("GeometricAntiProduct", anti_product.left_complement(algebra)),
+            // The reversal of the complement of b is the complement of the anti reversal of b
+            (
+                "AntiDotProduct",
+                anti_product
+                    .projected(|_r, _s, t| t == 0)
+                    .negated(|_r, s, _t| s % 4 >= 2)
+                    .left_complement(algebra),
+            ),
         ]
     }
 }
@@ -279,4 +663,5 @@ impl MultiVectorClassRegistry {
 pub struct MultiVectorClass {
     pub class_name: String,
     pub grouped_basis: Vec<Vec<BasisElement>>,
+    pub element_aliases: std::collections::HashMap<BasisElementIndex, String>,
 }
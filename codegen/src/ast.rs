@@ -3,6 +3,8 @@ use crate::algebra::MultiVectorClass;
 #[derive(PartialEq, Eq, Clone, Debug)]
 pub enum DataType<'a> {
     Integer,
+    /// Bit mask of grades, whose constants list the grades which are set
+    GradeMask,
     SimdVector(usize),
     MultiVector(&'a MultiVectorClass),
 }
@@ -17,6 +19,23 @@ impl DataType<'_> {
     }
 }
 
+/// The value of a `DataType::GradeMask` constant, which has one bit for each of the grades 0 to 64
+pub fn grade_mask(grades: &[isize]) -> u128 {
+    grades.iter().fold(0, |grade_mask, grade| grade_mask | 1 << grade)
+}
+
+/// Elementary functions of a scalar
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ScalarFunction {
+    Cosine,
+    Sine,
+    HyperbolicCosine,
+    HyperbolicSine,
+    HyperbolicArcTangent,
+    Exponential,
+    Logarithm,
+}
+
 #[derive(PartialEq, Eq, Clone, Debug)]
 pub enum ExpressionContent<'a> {
     None,
@@ -36,6 +55,9 @@ pub enum ExpressionContent<'a> {
     Gather(Box<Expression<'a>>, Vec<(usize, usize)>),
     Constant(DataType<'a>, Vec<isize>),
     SquareRoot(Box<Expression<'a>>),
+    Function(ScalarFunction, Box<Expression<'a>>),
+    /// The angle of the point `(x, y)`, given as `(y, x)`
+    ArcTangent2(Box<Expression<'a>>, Box<Expression<'a>>),
     Add(Box<Expression<'a>>, Box<Expression<'a>>),
     Subtract(Box<Expression<'a>>, Box<Expression<'a>>),
     Multiply(Box<Expression<'a>>, Box<Expression<'a>>),
@@ -52,7 +74,78 @@ pub struct Expression<'a> {
     pub content: ExpressionContent<'a>,
 }
 
-impl Expression<'_> {
+/// Trait, parameter types and result type of a trait method invoked by generated code
+pub type Invocation<'a> = (&'static str, Vec<DataType<'a>>, DataType<'a>);
+
+impl<'a> Expression<'a> {
+    /// If this expression has to be parenthesized when it is the left or right operand of `operator`
+    ///
+    /// Products and quotients as divisor are parenthesized, as `a / (b * c)` is not `a / b * c`.
+    pub fn needs_parentheses(&self, operator: &ExpressionContent, is_rhs: bool) -> bool {
+        match &self.content {
+            ExpressionContent::Select(_, _, _) => true,
+            ExpressionContent::Add(_, _) | ExpressionContent::Subtract(_, _) => {
+                matches!(operator, ExpressionContent::Multiply(_, _) | ExpressionContent::Divide(_, _))
+                    || (is_rhs && matches!(operator, ExpressionContent::Subtract(_, _)))
+            }
+            ExpressionContent::Multiply(_, _) | ExpressionContent::Divide(_, _) => is_rhs && matches!(operator, ExpressionContent::Divide(_, _)),
+            _ => false,
+        }
+    }
+
+    pub fn collect_invocations(&self, invocations: &mut Vec<Invocation<'a>>) {
+        match &self.content {
+            ExpressionContent::InvokeClassMethod(class, name, arguments) => {
+                if *name != "Constructor" {
+                    invocations.push((name, vec![], DataType::MultiVector(class)));
+                }
+                for (_, argument) in arguments.iter() {
+                    argument.collect_invocations(invocations);
+                }
+            }
+            ExpressionContent::InvokeInstanceMethod(data_type, inner_expression, name, result_data_type, arguments) => {
+                let mut parameters = vec![data_type.clone()];
+                parameters.extend(arguments.iter().map(|(argument_data_type, _)| argument_data_type.clone()));
+                invocations.push((name, parameters, result_data_type.clone()));
+                inner_expression.collect_invocations(invocations);
+                for (_, argument) in arguments.iter() {
+                    argument.collect_invocations(invocations);
+                }
+            }
+            ExpressionContent::Conversion(source_class, destination_class, inner_expression) => {
+                invocations.push((
+                    "Into",
+                    vec![DataType::MultiVector(source_class)],
+                    DataType::MultiVector(destination_class),
+                ));
+                inner_expression.collect_invocations(invocations);
+            }
+            ExpressionContent::Select(a, b, c) => {
+                a.collect_invocations(invocations);
+                b.collect_invocations(invocations);
+                c.collect_invocations(invocations);
+            }
+            ExpressionContent::Access(inner_expression, _)
+            | ExpressionContent::Swizzle(inner_expression, _)
+            | ExpressionContent::Gather(inner_expression, _)
+            | ExpressionContent::SquareRoot(inner_expression)
+            | ExpressionContent::Function(_, inner_expression) => inner_expression.collect_invocations(invocations),
+            ExpressionContent::ArcTangent2(lhs, rhs)
+            | ExpressionContent::Add(lhs, rhs)
+            | ExpressionContent::Subtract(lhs, rhs)
+            | ExpressionContent::Multiply(lhs, rhs)
+            | ExpressionContent::Divide(lhs, rhs)
+            | ExpressionContent::LessThan(lhs, rhs)
+            | ExpressionContent::Equal(lhs, rhs)
+            | ExpressionContent::LogicAnd(lhs, rhs)
+            | ExpressionContent::BitShiftRight(lhs, rhs) => {
+                lhs.collect_invocations(invocations);
+                rhs.collect_invocations(invocations);
+            }
+            ExpressionContent::None | ExpressionContent::Variable(_, _) | ExpressionContent::Constant(_, _) => {}
+        }
+    }
+
     pub fn is_scalar(&self) -> bool {
         if self.size > 1 {
             return false;
@@ -84,9 +177,19 @@ impl<'a> Parameter<'a> {
 #[derive(PartialEq, Eq, Clone)]
 pub enum AstNode<'a> {
     None,
-    Preamble,
+    Preamble {
+        runtime_crate: &'a str,
+    },
     ClassDefinition {
         class: &'a MultiVectorClass,
+        generator_names: &'a [String],
+    },
+    /// A trait which is specific to the algebra and therefore not part of the runtime crate
+    TraitDefinition {
+        name: &'static str,
+        documentation: String,
+        /// Whether the method takes a second parameter `other: T`
+        binary: bool,
     },
     ReturnStatement {
         expression: Box<Expression<'a>>,
@@ -110,3 +213,54 @@ pub enum AstNode<'a> {
         body: Vec<AstNode<'a>>,
     },
 }
+
+impl<'a> AstNode<'a> {
+    /// Collects the trait methods the generated code of this node calls
+    pub fn collect_invocations(&self, invocations: &mut Vec<Invocation<'a>>) {
+        match self {
+            AstNode::ReturnStatement { expression } | AstNode::VariableAssignment { expression, .. } => expression.collect_invocations(invocations),
+            AstNode::IfThenBlock { condition, body } | AstNode::WhileLoopBlock { condition, body } => {
+                condition.collect_invocations(invocations);
+                for statement in body.iter() {
+                    statement.collect_invocations(invocations);
+                }
+            }
+            AstNode::TraitImplementation { body, .. } => {
+                for statement in body.iter() {
+                    statement.collect_invocations(invocations);
+                }
+            }
+            AstNode::None | AstNode::Preamble { .. } | AstNode::ClassDefinition { .. } | AstNode::TraitDefinition { .. } => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variable(name: &'static str) -> Box<Expression<'static>> {
+        Box::new(Expression {
+            size: 1,
+            content: ExpressionContent::Variable(DataType::SimdVector(1), name),
+        })
+    }
+
+    #[test]
+    fn parenthesized_divisor() {
+        let product = Expression {
+            size: 1,
+            content: ExpressionContent::Multiply(variable("b"), variable("c")),
+        };
+        let quotient = Expression {
+            size: 1,
+            content: ExpressionContent::Divide(variable("b"), variable("c")),
+        };
+        let division = ExpressionContent::Divide(variable("a"), variable("b"));
+        let multiplication = ExpressionContent::Multiply(variable("a"), variable("b"));
+        assert!(product.needs_parentheses(&division, true));
+        assert!(quotient.needs_parentheses(&division, true));
+        assert!(!product.needs_parentheses(&division, false));
+        assert!(!quotient.needs_parentheses(&multiplication, true));
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
     algebra::{BasisElement, BasisElementIndex, Involution, MultiVectorClass, MultiVectorClassRegistry, Product},
-    ast::{AstNode, DataType, Expression, ExpressionContent, Parameter},
+    ast::{AstNode, DataType, Expression, ExpressionContent, Parameter, ScalarFunction},
 };
 
 #[macro_export]
@@ -141,6 +141,18 @@ pub fn simplify_and_legalize(expression: Box<Expression>) -> Box<Expression> {
     }
 }
 
+/// Negation of the `grades` of the product AC of a multivector A and its cofactor C, see `derive_general_inverse`
+pub struct GradeNegation<'b, 'a> {
+    pub grades: Vec<isize>,
+    /// Product AC
+    pub geometric_product: &'b AstNode<'a>,
+    pub grade_select: &'b AstNode<'a>,
+    pub scale: &'b AstNode<'a>,
+    pub subtraction: &'b AstNode<'a>,
+    /// Product of C with the negated AC
+    pub geometric_product_2: &'b AstNode<'a>,
+}
+
 impl MultiVectorClass {
     pub fn flat_basis(&self) -> Vec<BasisElement> {
         self.grouped_basis.iter().flatten().cloned().collect()
@@ -156,6 +168,13 @@ impl MultiVectorClass {
         signature
     }
 
+    /// `Some(0)` if all elements have an even grade, `Some(1)` if all have an odd grade and `None` otherwise
+    pub fn parity(&self) -> Option<usize> {
+        let mut parities = self.grouped_basis.iter().flatten().map(|element| element.grade() % 2);
+        let parity = parities.next()?;
+        parities.all(|other| other == parity).then_some(parity)
+    }
+
     pub fn index_in_group(&self, mut index: usize) -> (usize, usize) {
         for (group_index, group) in self.grouped_basis.iter().enumerate() {
             if index >= group.len() {
@@ -250,27 +269,8 @@ impl MultiVectorClass {
                         )
                     })
                     .unzip();
-                let a_group_index = a_indices[0].0;
-                let expression = Expression {
-                    size,
-                    content: ExpressionContent::Multiply(
-                        Box::new(Expression {
-                            size,
-                            content: ExpressionContent::Gather(
-                                Box::new(Expression {
-                                    size: parameter_a.multi_vector_class().grouped_basis[a_group_index].len(),
-                                    content: ExpressionContent::Variable(parameter_a.data_type.clone(), parameter_a.name),
-                                }),
-                                a_indices,
-                            ),
-                        }),
-                        Box::new(Expression {
-                            size,
-                            content: ExpressionContent::Constant(DataType::SimdVector(size), factors),
-                        }),
-                    ),
-                };
-                body.push((DataType::SimdVector(size), *simplify_and_legalize(Box::new(expression))));
+                // Degenerate metrics map whole groups to zero
+                body.push((DataType::SimdVector(size), Self::gathered_group(parameter_a, factors, a_indices)));
                 base_index += size;
             }
             AstNode::TraitImplementation {
@@ -291,6 +291,75 @@ impl MultiVectorClass {
         }
     }
 
+    /// Conversion into `result_class`, which contains all elements of `parameter_a` and whose other elements are zero
+    pub fn widening<'a>(name: &'static str, parameter_a: &Parameter<'a>, result_class: &'a MultiVectorClass) -> AstNode<'a> {
+        let a_flat_basis = parameter_a.multi_vector_class().flat_basis();
+        let result_flat_basis = result_class.flat_basis();
+        let mut body = Vec::new();
+        let mut base_index = 0;
+        for result_group in result_class.grouped_basis.iter() {
+            let size = result_group.len();
+            let (factors, a_indices): (Vec<_>, Vec<_>) = (0..size)
+                .map(|index_in_group| {
+                    let result_element = &result_flat_basis[base_index + index_in_group];
+                    match a_flat_basis.iter().position(|a_element| a_element.index == result_element.index) {
+                        Some(index_in_a) => (
+                            result_element.scalar * a_flat_basis[index_in_a].scalar,
+                            parameter_a.multi_vector_class().index_in_group(index_in_a),
+                        ),
+                        None => (0, (0, 0)),
+                    }
+                })
+                .unzip();
+            body.push((DataType::SimdVector(size), Self::gathered_group(parameter_a, factors, a_indices)));
+            base_index += size;
+        }
+        AstNode::TraitImplementation {
+            result: Parameter {
+                name,
+                data_type: DataType::MultiVector(result_class),
+            },
+            parameters: vec![parameter_a.clone()],
+            body: vec![AstNode::ReturnStatement {
+                expression: Box::new(Expression {
+                    size: 1,
+                    content: ExpressionContent::InvokeClassMethod(result_class, "Constructor", body),
+                }),
+            }],
+        }
+    }
+
+    /// Lanes `a_indices` of `parameter_a` multiplied by `factors`, or a constant if all factors are zero
+    fn gathered_group<'a>(parameter_a: &Parameter<'a>, factors: Vec<isize>, a_indices: Vec<(usize, usize)>) -> Expression<'a> {
+        let size = factors.len();
+        if factors.iter().all(|factor| *factor == 0) {
+            return *simplify_and_legalize(Box::new(Expression {
+                size,
+                content: ExpressionContent::Constant(DataType::SimdVector(size), factors),
+            }));
+        }
+        let a_group_index = a_indices[0].0;
+        *simplify_and_legalize(Box::new(Expression {
+            size,
+            content: ExpressionContent::Multiply(
+                Box::new(Expression {
+                    size,
+                    content: ExpressionContent::Gather(
+                        Box::new(Expression {
+                            size: parameter_a.multi_vector_class().grouped_basis[a_group_index].len(),
+                            content: ExpressionContent::Variable(parameter_a.data_type.clone(), parameter_a.name),
+                        }),
+                        a_indices,
+                    ),
+                }),
+                Box::new(Expression {
+                    size,
+                    content: ExpressionContent::Constant(DataType::SimdVector(size), factors),
+                }),
+            ),
+        }))
+    }
+
     pub fn element_wise<'a>(
         name: &'static str,
         parameter_a: &Parameter<'a>,
@@ -384,15 +453,9 @@ impl MultiVectorClass {
         }
     }
 
-    pub fn product<'a>(
-        name: &'static str,
-        product: &Product,
-        parameter_a: &Parameter<'a>,
-        parameter_b: &Parameter<'a>,
-        registry: &'a MultiVectorClassRegistry,
-    ) -> AstNode<'a> {
-        let a_flat_basis = parameter_a.multi_vector_class().flat_basis();
-        let b_flat_basis = parameter_b.multi_vector_class().flat_basis();
+    pub fn product_signature(product: &Product, class_a: &MultiVectorClass, class_b: &MultiVectorClass) -> Vec<BasisElementIndex> {
+        let a_flat_basis = class_a.flat_basis();
+        let b_flat_basis = class_b.flat_basis();
         let mut result_signature = std::collections::HashSet::new();
         for product_term in product.terms.iter() {
             if a_flat_basis.iter().any(|e| e.index == product_term.factor_a.index)
@@ -403,22 +466,38 @@ impl MultiVectorClass {
         }
         let mut result_signature = result_signature.into_iter().collect::<Vec<_>>();
         result_signature.sort_unstable();
+        result_signature
+    }
+
+    pub fn product<'a>(
+        name: &'static str,
+        product: &Product,
+        parameter_a: &Parameter<'a>,
+        parameter_b: &Parameter<'a>,
+        registry: &'a MultiVectorClassRegistry,
+    ) -> AstNode<'a> {
+        let a_flat_basis = parameter_a.multi_vector_class().flat_basis();
+        let b_flat_basis = parameter_b.multi_vector_class().flat_basis();
+        let result_signature = Self::product_signature(product, parameter_a.multi_vector_class(), parameter_b.multi_vector_class());
         if let Some(result_class) = registry.get(&result_signature) {
             let result_flat_basis = result_class.flat_basis();
-            let mut sorted_terms = vec![vec![(0, 0); a_flat_basis.len()]; result_flat_basis.len()];
+            // With a non-diagonal metric several elements of b can contribute to the same pair of result and a element
+            let mut sorted_terms = vec![vec![Vec::new(); a_flat_basis.len()]; result_flat_basis.len()];
             for product_term in product.terms.iter() {
                 if let Some(y) = result_flat_basis.iter().position(|e| e.index == product_term.product.index) {
                     if let Some(x) = a_flat_basis.iter().position(|e| e.index == product_term.factor_a.index) {
                         if let Some(gather_index) = b_flat_basis.iter().position(|e| e.index == product_term.factor_b.index) {
-                            sorted_terms[y][x] = (
-                                result_flat_basis[y].scalar
-                                    * product_term.product.scalar
-                                    * a_flat_basis[x].scalar
-                                    * product_term.factor_a.scalar
-                                    * b_flat_basis[gather_index].scalar
-                                    * product_term.factor_b.scalar,
-                                gather_index,
-                            );
+                            let factor = result_flat_basis[y].scalar
+                                * product_term.product.scalar
+                                * a_flat_basis[x].scalar
+                                * product_term.factor_a.scalar
+                                * b_flat_basis[gather_index].scalar
+                                * product_term.factor_b.scalar;
+                            let terms: &mut Vec<(isize, usize)> = &mut sorted_terms[y][x];
+                            match terms.iter_mut().find(|(_, index_in_b)| *index_in_b == gather_index) {
+                                Some(term) => term.0 += factor,
+                                None => terms.push((factor, gather_index)),
+                            }
                         }
                     }
                 }
@@ -434,7 +513,19 @@ impl MultiVectorClass {
                 let result_terms = (0..size)
                     .map(|index_in_group| &sorted_terms[base_index + index_in_group])
                     .collect::<Vec<_>>();
-                let transposed_terms = (0..result_terms[0].len()).map(|i| result_terms.iter().map(|inner| inner[i]).collect::<Vec<_>>());
+                let transposed_terms = (0..result_terms[0].len()).flat_map(|i| {
+                    let layers = result_terms.iter().map(|inner| inner[i].len()).max().unwrap().max(1);
+                    let result_terms = &result_terms;
+                    (0..layers).map(move |layer| {
+                        (
+                            i,
+                            result_terms
+                                .iter()
+                                .map(|inner| *inner[i].get(layer).unwrap_or(&(0, 0)))
+                                .collect::<Vec<_>>(),
+                        )
+                    })
+                });
                 let mut contraction = (
                     Expression {
                         size,
@@ -448,7 +539,7 @@ impl MultiVectorClass {
                     vec![(0, 0); expression.size],
                     vec![0; expression.size],
                 );
-                for (index_in_a, a_terms) in transposed_terms.enumerate() {
+                for (index_in_a, a_terms) in transposed_terms {
                     if a_terms.iter().all(|(factor, _)| *factor == 0) {
                         continue;
                     }
@@ -737,11 +828,118 @@ impl MultiVectorClass {
         }
     }
 
+    /// Square root of the antiscalar `parameter_a.anti_dot_product(parameter_a)`
+    pub fn derive_bulk_norm<'a>(name: &'static str, anti_dot_product: &AstNode<'a>, parameter_a: &Parameter<'a>) -> AstNode<'a> {
+        let anti_dot_product_result = result_of_trait!(anti_dot_product);
+        AstNode::TraitImplementation {
+            result: Parameter {
+                name,
+                data_type: anti_dot_product_result.data_type.clone(),
+            },
+            parameters: vec![parameter_a.clone()],
+            body: vec![AstNode::ReturnStatement {
+                expression: Box::new(Expression {
+                    size: 1,
+                    content: ExpressionContent::InvokeClassMethod(
+                        anti_dot_product_result.multi_vector_class(),
+                        "Constructor",
+                        vec![(
+                            DataType::SimdVector(1),
+                            Expression {
+                                size: 1,
+                                content: ExpressionContent::SquareRoot(Box::new(Expression {
+                                    size: 1,
+                                    content: ExpressionContent::Access(
+                                        Box::new(Expression {
+                                            size: 1,
+                                            content: ExpressionContent::InvokeInstanceMethod(
+                                                parameter_a.data_type.clone(),
+                                                Box::new(Expression {
+                                                    size: 1,
+                                                    content: ExpressionContent::Variable(parameter_a.data_type.clone(), parameter_a.name),
+                                                }),
+                                                anti_dot_product_result.name,
+                                                anti_dot_product_result.data_type.clone(),
+                                                vec![(
+                                                    parameter_a.data_type.clone(),
+                                                    Expression {
+                                                        size: 1,
+                                                        content: ExpressionContent::Variable(parameter_a.data_type.clone(), parameter_a.name),
+                                                    },
+                                                )],
+                                            ),
+                                        }),
+                                        0,
+                                    ),
+                                })),
+                            },
+                        )],
+                    ),
+                }),
+            }],
+        }
+    }
+
+    /// Ratio of the bulk norm to the weight norm, which is a distance for the elements of projective geometric algebras
+    pub fn derive_geometric_norm<'a>(
+        name: &'static str,
+        bulk_norm: &AstNode<'a>,
+        weight_norm: &AstNode<'a>,
+        parameter_a: &Parameter<'a>,
+    ) -> AstNode<'a> {
+        let bulk_norm_result = result_of_trait!(bulk_norm);
+        let weight_norm_result = result_of_trait!(weight_norm);
+        let norm = |norm_result: &Parameter<'a>| Expression {
+            size: 1,
+            content: ExpressionContent::Access(
+                Box::new(Expression {
+                    size: 1,
+                    content: ExpressionContent::InvokeInstanceMethod(
+                        parameter_a.data_type.clone(),
+                        Box::new(Expression {
+                            size: 1,
+                            content: ExpressionContent::Variable(parameter_a.data_type.clone(), parameter_a.name),
+                        }),
+                        norm_result.name,
+                        norm_result.data_type.clone(),
+                        vec![],
+                    ),
+                }),
+                0,
+            ),
+        };
+        AstNode::TraitImplementation {
+            result: Parameter {
+                name,
+                data_type: weight_norm_result.data_type.clone(),
+            },
+            parameters: vec![parameter_a.clone()],
+            body: vec![AstNode::ReturnStatement {
+                expression: Box::new(Expression {
+                    size: 1,
+                    content: ExpressionContent::InvokeClassMethod(
+                        weight_norm_result.multi_vector_class(),
+                        "Constructor",
+                        vec![(
+                            DataType::SimdVector(1),
+                            Expression {
+                                size: 1,
+                                content: ExpressionContent::Divide(Box::new(norm(bulk_norm_result)), Box::new(norm(weight_norm_result))),
+                            },
+                        )],
+                    ),
+                }),
+            }],
+        }
+    }
+
+    /// Scales `parameter_a` by the inverse of `magnitude`, where `parameter_b` is the scalar class `geometric_product` accepts
     pub fn derive_signum<'a>(
         name: &'static str,
         geometric_product: &AstNode<'a>,
         magnitude: &AstNode<'a>,
         parameter_a: &Parameter<'a>,
+        parameter_b: &Parameter<'a>,
     ) -> AstNode<'a> {
         let geometric_product_result = result_of_trait!(geometric_product);
         let magnitude_result = result_of_trait!(magnitude);
@@ -763,11 +961,11 @@ impl MultiVectorClass {
                         geometric_product_result.name,
                         geometric_product_result.data_type.clone(),
                         vec![(
-                            DataType::MultiVector(magnitude_result.multi_vector_class()),
+                            parameter_b.data_type.clone(),
                             Expression {
                                 size: 1,
                                 content: ExpressionContent::InvokeClassMethod(
-                                    magnitude_result.multi_vector_class(),
+                                    parameter_b.multi_vector_class(),
                                     "Constructor",
                                     vec![(
                                         DataType::SimdVector(1),
@@ -898,6 +1096,124 @@ impl MultiVectorClass {
         }
     }
 
+    /// Inverse of any element of the class of `parameter_a`, which is its adjugate divided by its determinant,
+    /// using the closed forms of Hitzer and Sangwine for up to five generators
+    ///
+    /// The cofactor C is the product of the `involutions` of `parameter_a`, Ā for one, two or four generators and ĀÂÃ for three or five.
+    /// With four or five generators the adjugate is C m(AC), where m negates the grades of `grade_negation`, else it is C itself.
+    /// The determinant ⟨A adj(A)⟩₀ is zero exactly if `parameter_a` has no inverse, which then has only non-finite elements for `TryInverse`.
+    /// `conversion` drops the elements of the adjugate which vanish anyway, as the involutions treat the inverse like `parameter_a`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn derive_general_inverse<'a>(
+        name: &'static str,
+        involutions: &[&AstNode<'a>],
+        cofactor_products: &[&AstNode<'a>],
+        grade_negation: Option<&GradeNegation<'_, 'a>>,
+        scalar_product: &AstNode<'a>,
+        scale: &AstNode<'a>,
+        conversion: Option<&AstNode<'a>>,
+        parameter_a: &Parameter<'a>,
+    ) -> AstNode<'a> {
+        let self_variable = || Expression {
+            size: 1,
+            content: ExpressionContent::Variable(parameter_a.data_type.clone(), parameter_a.name),
+        };
+        let variable = |ast_node: &AstNode<'a>, name| Expression {
+            size: 1,
+            content: ExpressionContent::Variable(result_of_trait!(ast_node).data_type.clone(), name),
+        };
+        let mut cofactor = invocation(involutions[0], self_variable(), vec![]);
+        for (involution, geometric_product) in involutions[1..].iter().zip(cofactor_products.iter()) {
+            cofactor = invocation(geometric_product, cofactor, vec![invocation(involution, self_variable(), vec![])]);
+        }
+        let cofactor_node = *cofactor_products.last().unwrap_or(&involutions[0]);
+        let mut body = Vec::new();
+        let (adjugate_node, adjugate) = if let Some(grade_negation) = grade_negation {
+            body.push(AstNode::VariableAssignment {
+                name: "cofactor",
+                data_type: Some(result_of_trait!(cofactor_node).data_type.clone()),
+                expression: Box::new(cofactor),
+            });
+            body.push(AstNode::VariableAssignment {
+                name: "product",
+                data_type: Some(result_of_trait!(grade_negation.geometric_product).data_type.clone()),
+                expression: Box::new(invocation(
+                    grade_negation.geometric_product,
+                    self_variable(),
+                    vec![variable(cofactor_node, "cofactor")],
+                )),
+            });
+            let selection = invocation(
+                grade_negation.grade_select,
+                variable(grade_negation.geometric_product, "product"),
+                vec![Expression {
+                    size: 1,
+                    content: ExpressionContent::Constant(DataType::GradeMask, grade_negation.grades.clone()),
+                }],
+            );
+            (
+                grade_negation.geometric_product_2,
+                invocation(
+                    grade_negation.geometric_product_2,
+                    variable(cofactor_node, "cofactor"),
+                    vec![invocation(
+                        grade_negation.subtraction,
+                        variable(grade_negation.geometric_product, "product"),
+                        vec![invocation(grade_negation.scale, selection, vec![scalar_constant(2)])],
+                    )],
+                ),
+            )
+        } else {
+            (cofactor_node, cofactor)
+        };
+        body.push(AstNode::VariableAssignment {
+            name: "adjugate",
+            data_type: Some(result_of_trait!(adjugate_node).data_type.clone()),
+            expression: Box::new(adjugate),
+        });
+        body.push(scalar_assignment(
+            "determinant",
+            Expression {
+                size: 1,
+                content: ExpressionContent::Access(
+                    Box::new(invocation(scalar_product, self_variable(), vec![variable(adjugate_node, "adjugate")])),
+                    0,
+                ),
+            },
+        ));
+        let scale_result = result_of_trait!(scale);
+        let mut inverse = invocation(
+            scale,
+            variable(adjugate_node, "adjugate"),
+            vec![scalar_operation(
+                ExpressionContent::Divide,
+                scalar_constant(1),
+                scalar_variable("determinant"),
+            )],
+        );
+        let mut data_type = scale_result.data_type.clone();
+        if let Some(conversion) = conversion {
+            let conversion_result = result_of_trait!(conversion);
+            inverse = Expression {
+                size: 1,
+                content: ExpressionContent::Conversion(
+                    scale_result.multi_vector_class(),
+                    conversion_result.multi_vector_class(),
+                    Box::new(inverse),
+                ),
+            };
+            data_type = conversion_result.data_type.clone();
+        }
+        body.push(AstNode::ReturnStatement {
+            expression: Box::new(inverse),
+        });
+        AstNode::TraitImplementation {
+            result: Parameter { name, data_type },
+            parameters: vec![parameter_a.clone()],
+            body,
+        }
+    }
+
     pub fn derive_power_of_integer<'a>(
         name: &'static str,
         geometric_product: &AstNode<'a>,
@@ -1130,6 +1446,106 @@ impl MultiVectorClass {
         }
     }
 
+    /// Zeroes the elements whose grade is not set in the bit mask `parameter_b`
+    pub fn derive_grade_select<'a>(name: &'static str, parameter_a: &Parameter<'a>, parameter_b: &Parameter<'a>) -> AstNode<'a> {
+        let class = parameter_a.multi_vector_class();
+        let mut grades = class.flat_basis().iter().map(|element| element.grade()).collect::<Vec<_>>();
+        grades.sort_unstable();
+        grades.dedup();
+        let mut body = vec![AstNode::VariableAssignment {
+            name: "result",
+            data_type: Some(parameter_a.data_type.clone()),
+            expression: Box::new(Expression {
+                size: 1,
+                content: ExpressionContent::Variable(parameter_a.data_type.clone(), parameter_a.name),
+            }),
+        }];
+        for grade in grades {
+            let groups = class
+                .grouped_basis
+                .iter()
+                .enumerate()
+                .map(|(group_index, group)| {
+                    let size = group.len();
+                    let factors = group.iter().map(|element| (element.grade() != grade) as isize).collect::<Vec<_>>();
+                    let expression = if factors.iter().all(|factor| *factor == 0) {
+                        Expression {
+                            size,
+                            content: ExpressionContent::Constant(DataType::SimdVector(size), factors),
+                        }
+                    } else {
+                        Expression {
+                            size,
+                            content: ExpressionContent::Multiply(
+                                Box::new(Expression {
+                                    size,
+                                    content: ExpressionContent::Gather(
+                                        Box::new(Expression {
+                                            size,
+                                            content: ExpressionContent::Variable(parameter_a.data_type.clone(), "result"),
+                                        }),
+                                        (0..size).map(|index_in_group| (group_index, index_in_group)).collect(),
+                                    ),
+                                }),
+                                Box::new(Expression {
+                                    size,
+                                    content: ExpressionContent::Constant(DataType::SimdVector(size), factors),
+                                }),
+                            ),
+                        }
+                    };
+                    (DataType::SimdVector(size), *simplify_and_legalize(Box::new(expression)))
+                })
+                .collect();
+            body.push(AstNode::IfThenBlock {
+                condition: Box::new(Expression {
+                    size: 1,
+                    content: ExpressionContent::Equal(
+                        Box::new(Expression {
+                            size: 1,
+                            content: ExpressionContent::LogicAnd(
+                                Box::new(Expression {
+                                    size: 1,
+                                    content: ExpressionContent::Variable(DataType::GradeMask, parameter_b.name),
+                                }),
+                                Box::new(Expression {
+                                    size: 1,
+                                    content: ExpressionContent::Constant(DataType::GradeMask, vec![grade as isize]),
+                                }),
+                            ),
+                        }),
+                        Box::new(Expression {
+                            size: 1,
+                            content: ExpressionContent::Constant(DataType::Integer, vec![0]),
+                        }),
+                    ),
+                }),
+                body: vec![AstNode::VariableAssignment {
+                    name: "result",
+                    data_type: None,
+                    expression: Box::new(Expression {
+                        size: 1,
+                        content: ExpressionContent::InvokeClassMethod(class, "Constructor", groups),
+                    }),
+                }],
+            });
+        }
+        body.push(AstNode::ReturnStatement {
+            expression: Box::new(Expression {
+                size: 1,
+                content: ExpressionContent::Variable(parameter_a.data_type.clone(), "result"),
+            }),
+        });
+        AstNode::TraitImplementation {
+            result: Parameter {
+                name,
+                data_type: parameter_a.data_type.clone(),
+            },
+            parameters: vec![parameter_a.clone(), parameter_b.clone()],
+            body,
+        }
+    }
+
     pub fn derive_division<'a>(
         name: &'static str,
         geometric_product: &AstNode<'a>,
@@ -1178,11 +1594,16 @@ impl MultiVectorClass {
         }
     }
 
+    /// `parameter_a.product(parameter_b).product(parameter_a.involution())`, optionally converted by `conversion`
+    ///
+    /// `widening` converts `parameter_a` to a class containing it first, if the product with its own class has no class.
+    #[allow(clippy::too_many_arguments)]
     pub fn derive_sandwich_product<'a>(
         name: &'static str,
         geometric_product: &AstNode<'a>,
         geometric_product_2: &AstNode<'a>,
         involution: &AstNode<'a>,
+        widening: Option<&AstNode<'a>>,
         conversion: Option<&AstNode<'a>>,
         parameter_a: &Parameter<'a>,
         parameter_b: &Parameter<'a>,
@@ -1190,6 +1611,17 @@ impl MultiVectorClass {
         let geometric_product_result = result_of_trait!(geometric_product);
         let geometric_product_2_result = result_of_trait!(geometric_product_2);
         let involution_result = result_of_trait!(involution);
+        let variable_a = || {
+            Self::widened(
+                Expression {
+                    size: 1,
+                    content: ExpressionContent::Variable(parameter_a.data_type.clone(), parameter_a.name),
+                },
+                parameter_a,
+                widening,
+            )
+        };
+        let (data_type_a, expression_a) = variable_a();
         let product = Box::new(Expression {
             size: 1,
             content: ExpressionContent::InvokeInstanceMethod(
@@ -1197,11 +1629,8 @@ impl MultiVectorClass {
                 Box::new(Expression {
                     size: 1,
                     content: ExpressionContent::InvokeInstanceMethod(
-                        parameter_a.data_type.clone(),
-                        Box::new(Expression {
-                            size: 1,
-                            content: ExpressionContent::Variable(parameter_a.data_type.clone(), parameter_a.name),
-                        }),
+                        data_type_a.clone(),
+                        Box::new(expression_a),
                         geometric_product_result.name,
                         geometric_product_result.data_type.clone(),
                         vec![(
@@ -1220,11 +1649,8 @@ impl MultiVectorClass {
                     Expression {
                         size: 1,
                         content: ExpressionContent::InvokeInstanceMethod(
-                            parameter_a.data_type.clone(),
-                            Box::new(Expression {
-                                size: 1,
-                                content: ExpressionContent::Variable(parameter_a.data_type.clone(), parameter_a.name),
-                            }),
+                            data_type_a,
+                            Box::new(variable_a().1),
                             involution_result.name,
                             involution_result.data_type.clone(),
                             vec![],
@@ -1260,4 +1686,1194 @@ impl MultiVectorClass {
             }],
         }
     }
+
+    /// `parameter_a.product(parameter_b).geometric_product(parameter_b.inverse())` converted to the class of `parameter_a`
+    ///
+    /// This is the projection onto `parameter_b` for the inner product and the rejection from it for the outer product.
+    /// `widening` converts `parameter_a` to a class containing it first, if there is no product of its own class.
+    #[allow(clippy::too_many_arguments)]
+    pub fn derive_projection<'a>(
+        name: &'static str,
+        product: &AstNode<'a>,
+        geometric_product: &AstNode<'a>,
+        inverse: &AstNode<'a>,
+        widening: Option<&AstNode<'a>>,
+        conversion: Option<&AstNode<'a>>,
+        parameter_a: &Parameter<'a>,
+        parameter_b: &Parameter<'a>,
+    ) -> AstNode<'a> {
+        let product_result = result_of_trait!(product);
+        let geometric_product_result = result_of_trait!(geometric_product);
+        let inverse_result = result_of_trait!(inverse);
+        let (data_type_a, expression_a) = Self::widened(
+            Expression {
+                size: 1,
+                content: ExpressionContent::Variable(parameter_a.data_type.clone(), parameter_a.name),
+            },
+            parameter_a,
+            widening,
+        );
+        let projection = Box::new(Expression {
+            size: 1,
+            content: ExpressionContent::InvokeInstanceMethod(
+                product_result.data_type.clone(),
+                Box::new(Expression {
+                    size: 1,
+                    content: ExpressionContent::InvokeInstanceMethod(
+                        data_type_a,
+                        Box::new(expression_a),
+                        product_result.name,
+                        product_result.data_type.clone(),
+                        vec![(
+                            parameter_b.data_type.clone(),
+                            Expression {
+                                size: 1,
+                                content: ExpressionContent::Variable(parameter_b.data_type.clone(), parameter_b.name),
+                            },
+                        )],
+                    ),
+                }),
+                geometric_product_result.name,
+                geometric_product_result.data_type.clone(),
+                vec![(
+                    inverse_result.data_type.clone(),
+                    Expression {
+                        size: 1,
+                        content: ExpressionContent::InvokeInstanceMethod(
+                            parameter_b.data_type.clone(),
+                            Box::new(Expression {
+                                size: 1,
+                                content: ExpressionContent::Variable(parameter_b.data_type.clone(), parameter_b.name),
+                            }),
+                            inverse_result.name,
+                            inverse_result.data_type.clone(),
+                            vec![],
+                        ),
+                    },
+                )],
+            ),
+        });
+        Self::derive_converted(name, projection, geometric_product_result, conversion, parameter_a, parameter_b)
+    }
+
+    /// `parameter_b.geometric_product(parameter_a).geometric_product(parameter_b.inverse())` converted to the class of `parameter_a`
+    ///
+    /// For an odd versor `parameter_b` the `automorphism` is applied to `parameter_a`, so that e.g. a point reflected in a plane stays a point.
+    /// `widening` is as in `derive_projection`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn derive_reflection<'a>(
+        name: &'static str,
+        geometric_product: &AstNode<'a>,
+        geometric_product_2: &AstNode<'a>,
+        automorphism: Option<&AstNode<'a>>,
+        inverse: &AstNode<'a>,
+        widening: Option<&AstNode<'a>>,
+        conversion: Option<&AstNode<'a>>,
+        parameter_a: &Parameter<'a>,
+        parameter_b: &Parameter<'a>,
+    ) -> AstNode<'a> {
+        let geometric_product_result = result_of_trait!(geometric_product);
+        let geometric_product_2_result = result_of_trait!(geometric_product_2);
+        let inverse_result = result_of_trait!(inverse);
+        let variable_a = Expression {
+            size: 1,
+            content: ExpressionContent::Variable(parameter_a.data_type.clone(), parameter_a.name),
+        };
+        let argument = if let Some(automorphism) = automorphism {
+            let automorphism_result = result_of_trait!(automorphism);
+            Expression {
+                size: 1,
+                content: ExpressionContent::InvokeInstanceMethod(
+                    parameter_a.data_type.clone(),
+                    Box::new(variable_a),
+                    automorphism_result.name,
+                    automorphism_result.data_type.clone(),
+                    vec![],
+                ),
+            }
+        } else {
+            variable_a
+        };
+        let reflection = Box::new(Expression {
+            size: 1,
+            content: ExpressionContent::InvokeInstanceMethod(
+                geometric_product_result.data_type.clone(),
+                Box::new(Expression {
+                    size: 1,
+                    content: ExpressionContent::InvokeInstanceMethod(
+                        parameter_b.data_type.clone(),
+                        Box::new(Expression {
+                            size: 1,
+                            content: ExpressionContent::Variable(parameter_b.data_type.clone(), parameter_b.name),
+                        }),
+                        geometric_product_result.name,
+                        geometric_product_result.data_type.clone(),
+                        vec![Self::widened(argument, parameter_a, widening)],
+                    ),
+                }),
+                geometric_product_2_result.name,
+                geometric_product_2_result.data_type.clone(),
+                vec![(
+                    inverse_result.data_type.clone(),
+                    Expression {
+                        size: 1,
+                        content: ExpressionContent::InvokeInstanceMethod(
+                            parameter_b.data_type.clone(),
+                            Box::new(Expression {
+                                size: 1,
+                                content: ExpressionContent::Variable(parameter_b.data_type.clone(), parameter_b.name),
+                            }),
+                            inverse_result.name,
+                            inverse_result.data_type.clone(),
+                            vec![],
+                        ),
+                    },
+                )],
+            ),
+        });
+        Self::derive_converted(name, reflection, geometric_product_2_result, conversion, parameter_a, parameter_b)
+    }
+
+    /// `parameter_a - parameter_a.projection(parameter_b)`, where `projection` is the projection onto or the rejection from `parameter_b`
+    ///
+    /// This is the other one of the two if one of the parameters is a vector, as the geometric product with a vector
+    /// is the sum of the inner and the outer product.
+    pub fn derive_projection_remainder<'a>(
+        name: &'static str,
+        projection: &AstNode<'a>,
+        subtraction: &AstNode<'a>,
+        parameter_a: &Parameter<'a>,
+        parameter_b: &Parameter<'a>,
+    ) -> AstNode<'a> {
+        let projection_result = result_of_trait!(projection);
+        let subtraction_result = result_of_trait!(subtraction);
+        let variable_a = Expression {
+            size: 1,
+            content: ExpressionContent::Variable(parameter_a.data_type.clone(), parameter_a.name),
+        };
+        AstNode::TraitImplementation {
+            result: Parameter {
+                name,
+                data_type: subtraction_result.data_type.clone(),
+            },
+            parameters: vec![parameter_a.clone(), parameter_b.clone()],
+            body: vec![AstNode::ReturnStatement {
+                expression: Box::new(Expression {
+                    size: 1,
+                    content: ExpressionContent::InvokeInstanceMethod(
+                        parameter_a.data_type.clone(),
+                        Box::new(variable_a.clone()),
+                        subtraction_result.name,
+                        subtraction_result.data_type.clone(),
+                        vec![(
+                            projection_result.data_type.clone(),
+                            Expression {
+                                size: 1,
+                                content: ExpressionContent::InvokeInstanceMethod(
+                                    parameter_a.data_type.clone(),
+                                    Box::new(variable_a),
+                                    projection_result.name,
+                                    projection_result.data_type.clone(),
+                                    vec![(
+                                        parameter_b.data_type.clone(),
+                                        Expression {
+                                            size: 1,
+                                            content: ExpressionContent::Variable(parameter_b.data_type.clone(), parameter_b.name),
+                                        },
+                                    )],
+                                ),
+                            },
+                        )],
+                    ),
+                }),
+            }],
+        }
+    }
+
+    /// Applies the optional conversion `widening` to an `expression` of the class of `parameter_a`
+    fn widened<'a>(expression: Expression<'a>, parameter_a: &Parameter<'a>, widening: Option<&AstNode<'a>>) -> (DataType<'a>, Expression<'a>) {
+        if let Some(widening) = widening {
+            let widening_result = result_of_trait!(widening);
+            (
+                widening_result.data_type.clone(),
+                Expression {
+                    size: 1,
+                    content: ExpressionContent::Conversion(
+                        parameter_a.multi_vector_class(),
+                        widening_result.multi_vector_class(),
+                        Box::new(expression),
+                    ),
+                },
+            )
+        } else {
+            (parameter_a.data_type.clone(), expression)
+        }
+    }
+
+    fn derive_converted<'a>(
+        name: &'static str,
+        expression: Box<Expression<'a>>,
+        expression_result: &Parameter<'a>,
+        conversion: Option<&AstNode<'a>>,
+        parameter_a: &Parameter<'a>,
+        parameter_b: &Parameter<'a>,
+    ) -> AstNode<'a> {
+        AstNode::TraitImplementation {
+            result: Parameter {
+                name,
+                data_type: parameter_a.data_type.clone(),
+            },
+            parameters: vec![parameter_a.clone(), parameter_b.clone()],
+            body: vec![AstNode::ReturnStatement {
+                expression: if let Some(conversion) = conversion {
+                    Box::new(Expression {
+                        size: 1,
+                        content: ExpressionContent::Conversion(
+                            expression_result.multi_vector_class(),
+                            result_of_trait!(conversion).multi_vector_class(),
+                            expression,
+                        ),
+                    })
+                } else {
+                    expression
+                },
+            }],
+        }
+    }
+
+    /// Number of commuting simple bivectors the grade 2 part of this class splits into,
+    /// which is the largest number of its basis bivectors without a common generator
+    pub fn bivector_rank(&self) -> usize {
+        fn rank(bivectors: &[BasisElementIndex], used: BasisElementIndex) -> usize {
+            bivectors
+                .iter()
+                .enumerate()
+                .filter(|(_, index)| *index & used == 0)
+                .map(|(i, index)| 1 + rank(&bivectors[i + 1..], used | index))
+                .max()
+                .unwrap_or(0)
+        }
+        let bivectors = self.signature().into_iter().filter(|index| index.count_ones() == 2).collect::<Vec<_>>();
+        rank(&bivectors, 0)
+    }
+
+    /// Exponential of the bivector `parameter_a`, which is converted into the even class of `geometric_product` by `widening`
+    ///
+    /// A bivector of `bivector_rank` two is the sum of two commuting simple bivectors, whose squares are the scalars λ₁ and λ₂.
+    /// They are the roots of λ² - ⟨B²⟩₀λ + ¼(⟨B⁴⟩₀ - ⟨B²⟩₀²), so the parts never have to be separated.
+    /// Each factor exp(Bᵢ) is cos(√-λᵢ) + sin(√-λᵢ) / √-λᵢ Bᵢ, with cosh and sinh for positive λᵢ and 1 + Bᵢ for zero.
+    #[allow(clippy::too_many_arguments)]
+    pub fn derive_exponential<'a>(
+        name: &'static str,
+        widening: &AstNode<'a>,
+        geometric_product: &AstNode<'a>,
+        scale: &AstNode<'a>,
+        scalar_addition: &AstNode<'a>,
+        addition: &AstNode<'a>,
+        bivector_rank: usize,
+        parameter_a: &Parameter<'a>,
+    ) -> AstNode<'a> {
+        let widening_result = result_of_trait!(widening);
+        let versor_class = widening_result.multi_vector_class();
+        let variable = |name| Expression {
+            size: 1,
+            content: ExpressionContent::Variable(widening_result.data_type.clone(), name),
+        };
+        let mut body = vec![AstNode::VariableAssignment {
+            name: "bivector",
+            data_type: Some(widening_result.data_type.clone()),
+            expression: Box::new(Expression {
+                size: 1,
+                content: ExpressionContent::Conversion(
+                    parameter_a.multi_vector_class(),
+                    versor_class,
+                    Box::new(Expression {
+                        size: 1,
+                        content: ExpressionContent::Variable(parameter_a.data_type.clone(), parameter_a.name),
+                    }),
+                ),
+            }),
+        }];
+        let exponential = if bivector_rank == 1 {
+            body.push(scalar_assignment(
+                "square",
+                versor_class.scalar_part(invocation(geometric_product, variable("bivector"), vec![variable("bivector")])),
+            ));
+            body.push(scalar_assignment("cosine", generalized_cosine(scalar_variable("square"))));
+            body.push(scalar_assignment("sinc", generalized_sinc(scalar_variable("square"))));
+            invocation(
+                scalar_addition,
+                invocation(scale, variable("bivector"), vec![scalar_variable("sinc")]),
+                vec![scalar_variable("cosine")],
+            )
+        } else {
+            Self::push_bivector_squares(&mut body, versor_class, &widening_result.data_type, geometric_product, "bivector");
+            body.push(AstNode::VariableAssignment {
+                name: "cube",
+                data_type: Some(widening_result.data_type.clone()),
+                expression: Box::new(invocation(geometric_product, variable("square"), vec![variable("bivector")])),
+            });
+            for (cosine, sinc, square) in [("cosine_1", "sinc_1", "square_1"), ("cosine_2", "sinc_2", "square_2")] {
+                body.push(scalar_assignment(cosine, generalized_cosine(scalar_variable(square))));
+                body.push(scalar_assignment(sinc, generalized_sinc(scalar_variable(square))));
+            }
+            // exp(B₁) exp(B₂) = c₁c₂ + f₁c₂B₁ + c₁f₂B₂ + f₁f₂B₁B₂, where B₁B₂ = ½(B² - ⟨B²⟩₀) and B₂ = (B³ - (λ₁ + 3λ₂)B) / 2(λ₁ - λ₂)
+            body.push(scalar_assignment(
+                "mixed",
+                select_if_zero(
+                    scalar_variable("root"),
+                    scalar_constant(0),
+                    scalar_operation(
+                        ExpressionContent::Divide,
+                        scalar_operation(
+                            ExpressionContent::Divide,
+                            scalar_operation(
+                                ExpressionContent::Subtract,
+                                scalar_operation(ExpressionContent::Multiply, scalar_variable("cosine_1"), scalar_variable("sinc_2")),
+                                scalar_operation(ExpressionContent::Multiply, scalar_variable("sinc_1"), scalar_variable("cosine_2")),
+                            ),
+                            scalar_variable("root"),
+                        ),
+                        scalar_constant(2),
+                    ),
+                ),
+            ));
+            let half_trace = |factor| {
+                scalar_operation(
+                    ExpressionContent::Divide,
+                    scalar_operation(ExpressionContent::Multiply, factor, scalar_variable("trace")),
+                    scalar_constant(2),
+                )
+            };
+            let sinc_product = || scalar_operation(ExpressionContent::Multiply, scalar_variable("sinc_1"), scalar_variable("sinc_2"));
+            let linear = scalar_operation(
+                ExpressionContent::Subtract,
+                scalar_operation(
+                    ExpressionContent::Subtract,
+                    scalar_operation(ExpressionContent::Multiply, scalar_variable("sinc_1"), scalar_variable("cosine_2")),
+                    scalar_operation(ExpressionContent::Multiply, scalar_variable("mixed"), scalar_variable("square_2")),
+                ),
+                half_trace(scalar_variable("mixed")),
+            );
+            let mut exponential = invocation(scale, variable("bivector"), vec![linear]);
+            for (power, factor) in [
+                ("square", scalar_operation(ExpressionContent::Divide, sinc_product(), scalar_constant(2))),
+                (
+                    "cube",
+                    scalar_operation(ExpressionContent::Divide, scalar_variable("mixed"), scalar_constant(2)),
+                ),
+            ] {
+                exponential = invocation(addition, exponential, vec![invocation(scale, variable(power), vec![factor])]);
+            }
+            invocation(
+                scalar_addition,
+                exponential,
+                vec![scalar_operation(
+                    ExpressionContent::Subtract,
+                    scalar_operation(ExpressionContent::Multiply, scalar_variable("cosine_1"), scalar_variable("cosine_2")),
+                    half_trace(sinc_product()),
+                )],
+            )
+        };
+        body.push(AstNode::ReturnStatement {
+            expression: Box::new(exponential),
+        });
+        AstNode::TraitImplementation {
+            result: Parameter {
+                name,
+                data_type: widening_result.data_type.clone(),
+            },
+            parameters: vec![parameter_a.clone()],
+            body,
+        }
+    }
+
+    /// Logarithm of the even versor `parameter_a`, the inverse of `derive_exponential` for the bivector class of `projection`
+    ///
+    /// For `bivector_rank` two the bivector part P is split into the commuting parts P₁ and P₂, whose squares are μ₁ ≥ μ₂.
+    /// With the scalar part a and the grade 4 part Q, a² - μ₂ and aP₁ - QP₂ are c₁ and f₁B₁ times c₁, while a and P₂ are c₂ and f₂B₂ times c₁.
+    /// So c₁ is assumed to be positive, which only picks one of the logarithms of a double rotation.
+    #[allow(clippy::too_many_arguments)]
+    pub fn derive_logarithm<'a>(
+        name: &'static str,
+        projection: &AstNode<'a>,
+        geometric_product: &AstNode<'a>,
+        scale: &AstNode<'a>,
+        addition: &AstNode<'a>,
+        subtraction: &AstNode<'a>,
+        bivector_rank: usize,
+        parameter_a: &Parameter<'a>,
+    ) -> AstNode<'a> {
+        let projection_result = result_of_trait!(projection);
+        let versor_class = parameter_a.multi_vector_class();
+        let bivector_class = projection_result.multi_vector_class();
+        let variable = |name| Expression {
+            size: 1,
+            content: ExpressionContent::Variable(parameter_a.data_type.clone(), name),
+        };
+        let projected = |expression| Expression {
+            size: 1,
+            content: ExpressionContent::Conversion(versor_class, bivector_class, Box::new(expression)),
+        };
+        let mut body = vec![
+            scalar_assignment("scalar", versor_class.scalar_part(variable(parameter_a.name))),
+            AstNode::VariableAssignment {
+                name: "bivector",
+                data_type: Some(parameter_a.data_type.clone()),
+                expression: Box::new(Expression {
+                    size: 1,
+                    content: ExpressionContent::Conversion(bivector_class, versor_class, Box::new(projected(variable(parameter_a.name)))),
+                }),
+            },
+        ];
+        let logarithm = if bivector_rank == 1 {
+            body.push(scalar_assignment(
+                "square",
+                versor_class.scalar_part(invocation(geometric_product, variable("bivector"), vec![variable("bivector")])),
+            ));
+            invocation(
+                scale,
+                variable("bivector"),
+                vec![inverse_generalized_sinc(scalar_variable("scalar"), scalar_variable("square"))],
+            )
+        } else {
+            Self::push_bivector_squares(&mut body, versor_class, &parameter_a.data_type, geometric_product, "bivector");
+            // Both parts have the same square, so R = c² + cfP + f²B₁B₂ and c² - f²λ is cos, cosh or 1 of twice the angle
+            body.push(AstNode::IfThenBlock {
+                condition: Box::new(scalar_operation(ExpressionContent::Equal, scalar_variable("root"), scalar_constant(0))),
+                body: vec![AstNode::ReturnStatement {
+                    expression: Box::new(projected(invocation(
+                        scale,
+                        variable("bivector"),
+                        vec![inverse_generalized_sinc(
+                            scalar_operation(
+                                ExpressionContent::Subtract,
+                                scalar_operation(ExpressionContent::Multiply, scalar_variable("scalar"), scalar_constant(2)),
+                                scalar_constant(1),
+                            ),
+                            scalar_operation(ExpressionContent::Multiply, scalar_variable("square_1"), scalar_constant(4)),
+                        )],
+                    ))),
+                }],
+            });
+            // P₁ = ((3μ₁ + μ₂)P - P³) / 2(μ₁ - μ₂)
+            body.push(AstNode::VariableAssignment {
+                name: "part_1",
+                data_type: Some(parameter_a.data_type.clone()),
+                expression: Box::new(invocation(
+                    scale,
+                    invocation(
+                        subtraction,
+                        invocation(
+                            scale,
+                            variable("bivector"),
+                            vec![scalar_operation(
+                                ExpressionContent::Add,
+                                scalar_operation(ExpressionContent::Multiply, scalar_variable("square_1"), scalar_constant(3)),
+                                scalar_variable("square_2"),
+                            )],
+                        ),
+                        vec![invocation(geometric_product, variable("square"), vec![variable("bivector")])],
+                    ),
+                    vec![scalar_operation(
+                        ExpressionContent::Divide,
+                        scalar_operation(ExpressionContent::Divide, scalar_constant(1), scalar_variable("root")),
+                        scalar_constant(4),
+                    )],
+                )),
+            });
+            body.push(AstNode::VariableAssignment {
+                name: "part_2",
+                data_type: Some(parameter_a.data_type.clone()),
+                expression: Box::new(invocation(subtraction, variable("bivector"), vec![variable("part_1")])),
+            });
+            // aP₁ - QP₂ = aP + (P - R)P₂, as Q = R - a - P
+            body.push(AstNode::VariableAssignment {
+                name: "scaled_part_1",
+                data_type: Some(parameter_a.data_type.clone()),
+                expression: Box::new(invocation(
+                    addition,
+                    invocation(scale, variable("bivector"), vec![scalar_variable("scalar")]),
+                    vec![invocation(
+                        geometric_product,
+                        invocation(subtraction, variable("bivector"), vec![variable(parameter_a.name)]),
+                        vec![variable("part_2")],
+                    )],
+                )),
+            });
+            body.push(scalar_assignment(
+                "scaled_cosine_1",
+                scalar_operation(
+                    ExpressionContent::Subtract,
+                    scalar_operation(ExpressionContent::Multiply, scalar_variable("scalar"), scalar_variable("scalar")),
+                    scalar_variable("square_2"),
+                ),
+            ));
+            body.push(scalar_assignment(
+                "scaled_square_1",
+                versor_class.scalar_part(invocation(geometric_product, variable("scaled_part_1"), vec![variable("scaled_part_1")])),
+            ));
+            invocation(
+                addition,
+                invocation(
+                    scale,
+                    variable("scaled_part_1"),
+                    vec![inverse_generalized_sinc(
+                        scalar_variable("scaled_cosine_1"),
+                        scalar_variable("scaled_square_1"),
+                    )],
+                ),
+                vec![invocation(
+                    scale,
+                    variable("part_2"),
+                    vec![inverse_generalized_sinc(scalar_variable("scalar"), scalar_variable("square_2"))],
+                )],
+            )
+        };
+        body.push(AstNode::ReturnStatement {
+            expression: Box::new(projected(logarithm)),
+        });
+        AstNode::TraitImplementation {
+            result: Parameter {
+                name,
+                data_type: projection_result.data_type.clone(),
+            },
+            parameters: vec![parameter_a.clone()],
+            body,
+        }
+    }
+
+    /// `parameter_a.logarithm().geometric_product(parameter_b).exponential()`
+    pub fn derive_power_of_float<'a>(
+        name: &'static str,
+        logarithm: &AstNode<'a>,
+        scale: &AstNode<'a>,
+        exponential: &AstNode<'a>,
+        parameter_a: &Parameter<'a>,
+        parameter_b: &Parameter<'a>,
+    ) -> AstNode<'a> {
+        let exponential_result = result_of_trait!(exponential);
+        let logarithm = invocation(
+            logarithm,
+            Expression {
+                size: 1,
+                content: ExpressionContent::Variable(parameter_a.data_type.clone(), parameter_a.name),
+            },
+            vec![],
+        );
+        let scaled = invocation(
+            scale,
+            logarithm,
+            vec![Expression {
+                size: 1,
+                content: ExpressionContent::Variable(parameter_b.data_type.clone(), parameter_b.name),
+            }],
+        );
+        AstNode::TraitImplementation {
+            result: Parameter {
+                name,
+                data_type: exponential_result.data_type.clone(),
+            },
+            parameters: vec![parameter_a.clone(), parameter_b.clone()],
+            body: vec![AstNode::ReturnStatement {
+                expression: Box::new(invocation(exponential, scaled, vec![])),
+            }],
+        }
+    }
+
+    /// Exponential of `parameter_a`, the sum of a scalar a and a simple bivector B, as e^a exp(B) in the same class
+    ///
+    /// This is for classes without a class of their bivector part, like the complex numbers.
+    pub fn derive_complex_exponential<'a>(
+        name: &'static str,
+        subtraction: &AstNode<'a>,
+        geometric_product: &AstNode<'a>,
+        scale: &AstNode<'a>,
+        scalar_addition: &AstNode<'a>,
+        parameter_a: &Parameter<'a>,
+    ) -> AstNode<'a> {
+        let mut body = Self::split_scalar_and_bivector(subtraction, geometric_product, parameter_a);
+        body.push(scalar_assignment("cosine", generalized_cosine(scalar_variable("square"))));
+        body.push(scalar_assignment("sinc", generalized_sinc(scalar_variable("square"))));
+        let variable = |name| Expression {
+            size: 1,
+            content: ExpressionContent::Variable(parameter_a.data_type.clone(), name),
+        };
+        body.push(AstNode::ReturnStatement {
+            expression: Box::new(invocation(
+                scale,
+                invocation(
+                    scalar_addition,
+                    invocation(scale, variable("bivector"), vec![scalar_variable("sinc")]),
+                    vec![scalar_variable("cosine")],
+                ),
+                vec![scalar_function(ScalarFunction::Exponential, scalar_variable("scalar"))],
+            )),
+        });
+        AstNode::TraitImplementation {
+            result: Parameter {
+                name,
+                data_type: parameter_a.data_type.clone(),
+            },
+            parameters: vec![parameter_a.clone()],
+            body,
+        }
+    }
+
+    /// Logarithm of `parameter_a`, the inverse of `derive_complex_exponential`, whose scalar part is the logarithm of the `magnitude`
+    pub fn derive_complex_logarithm<'a>(
+        name: &'static str,
+        magnitude: &AstNode<'a>,
+        subtraction: &AstNode<'a>,
+        geometric_product: &AstNode<'a>,
+        scale: &AstNode<'a>,
+        scalar_addition: &AstNode<'a>,
+        parameter_a: &Parameter<'a>,
+    ) -> AstNode<'a> {
+        let mut body = Self::split_scalar_and_bivector(subtraction, geometric_product, parameter_a);
+        let variable = |name| Expression {
+            size: 1,
+            content: ExpressionContent::Variable(parameter_a.data_type.clone(), name),
+        };
+        body.push(AstNode::ReturnStatement {
+            expression: Box::new(invocation(
+                scalar_addition,
+                invocation(
+                    scale,
+                    variable("bivector"),
+                    vec![inverse_generalized_sinc(scalar_variable("scalar"), scalar_variable("square"))],
+                ),
+                vec![scalar_function(
+                    ScalarFunction::Logarithm,
+                    invocation(magnitude, variable(parameter_a.name), vec![]),
+                )],
+            )),
+        });
+        AstNode::TraitImplementation {
+            result: Parameter {
+                name,
+                data_type: parameter_a.data_type.clone(),
+            },
+            parameters: vec![parameter_a.clone()],
+            body,
+        }
+    }
+
+    /// Assigns the scalar part of `parameter_a` to `scalar`, the rest to `bivector` and the square of the rest to `square`
+    fn split_scalar_and_bivector<'a>(subtraction: &AstNode<'a>, geometric_product: &AstNode<'a>, parameter_a: &Parameter<'a>) -> Vec<AstNode<'a>> {
+        let class_a = parameter_a.multi_vector_class();
+        let variable = |name| Expression {
+            size: 1,
+            content: ExpressionContent::Variable(parameter_a.data_type.clone(), name),
+        };
+        vec![
+            scalar_assignment("scalar", class_a.scalar_part(variable(parameter_a.name))),
+            AstNode::VariableAssignment {
+                name: "bivector",
+                data_type: Some(parameter_a.data_type.clone()),
+                expression: Box::new(invocation(subtraction, variable(parameter_a.name), vec![scalar_variable("scalar")])),
+            },
+            scalar_assignment(
+                "square",
+                class_a.scalar_part(invocation(geometric_product, variable("bivector"), vec![variable("bivector")])),
+            ),
+        ]
+    }
+
+    /// Square root of the even versor `parameter_a`, which is its sum with its magnitude, normalized
+    ///
+    /// For a versor R of magnitude m, (m + R)² = 2(m + ⟨R⟩₀)R, so this is (m + R) / √(2(m + ⟨R⟩₀)) and (1 + R) / |1 + R| if m is one.
+    /// If the class contains a `quadvector` J, which the reversal fixes, R + R̃ has a part of J, as for the motors of projective
+    /// geometric algebras, whose J is the pseudoscalar. As J commutes with the class, m = √(RR̃) and the denominator
+    /// are study numbers a + bJ then, given the square of J.
+    /// Versors without a unique square root, like the rotation by a full turn, result in non-finite values.
+    #[allow(clippy::too_many_arguments)]
+    pub fn derive_square_root<'a>(
+        name: &'static str,
+        magnitude: &AstNode<'a>,
+        geometric_product: &AstNode<'a>,
+        reversal: &AstNode<'a>,
+        addition: &AstNode<'a>,
+        scalar_addition: &AstNode<'a>,
+        scale: &AstNode<'a>,
+        quadvector: Option<(BasisElementIndex, isize)>,
+        parameter_a: &Parameter<'a>,
+    ) -> AstNode<'a> {
+        let versor_class = parameter_a.multi_vector_class();
+        let variable = |name| Expression {
+            size: 1,
+            content: ExpressionContent::Variable(parameter_a.data_type.clone(), name),
+        };
+        let sum = |expression| AstNode::VariableAssignment {
+            name: "sum",
+            data_type: Some(parameter_a.data_type.clone()),
+            expression: Box::new(expression),
+        };
+        let (mut body, result) = if let Some((quadvector_index, quadvector_square)) = quadvector {
+            let scalar_and_quadvector_part = |name| {
+                (
+                    versor_class.scalar_part(variable(name)),
+                    versor_class.element_part(variable(name), quadvector_index),
+                )
+            };
+            let (squared_scalar, squared_quadvector) = scalar_and_quadvector_part("squared");
+            let (sum_scalar, sum_quadvector) = scalar_and_quadvector_part("sum");
+            let body = vec![
+                AstNode::VariableAssignment {
+                    name: "squared",
+                    data_type: Some(parameter_a.data_type.clone()),
+                    expression: Box::new(invocation(
+                        geometric_product,
+                        variable(parameter_a.name),
+                        vec![invocation(reversal, variable(parameter_a.name), vec![])],
+                    )),
+                },
+                scalar_assignment(
+                    "norm_scalar",
+                    study_square_root(squared_scalar, squared_quadvector.clone(), quadvector_square),
+                ),
+                scalar_assignment(
+                    "norm_quadvector",
+                    scalar_operation(
+                        ExpressionContent::Divide,
+                        scalar_operation(ExpressionContent::Divide, squared_quadvector, scalar_constant(2)),
+                        scalar_variable("norm_scalar"),
+                    ),
+                ),
+                sum(invocation(
+                    addition,
+                    variable(parameter_a.name),
+                    vec![versor_class.study_number(scalar_variable("norm_scalar"), scalar_variable("norm_quadvector"), quadvector_index)],
+                )),
+                // The square root of 2(⟨m + R⟩₀ + ⟨m + R⟩₄)
+                scalar_assignment(
+                    "denominator_scalar",
+                    study_square_root(
+                        scalar_operation(ExpressionContent::Multiply, sum_scalar, scalar_constant(2)),
+                        scalar_operation(ExpressionContent::Multiply, sum_quadvector.clone(), scalar_constant(2)),
+                        quadvector_square,
+                    ),
+                ),
+                scalar_assignment(
+                    "denominator_quadvector",
+                    scalar_operation(ExpressionContent::Divide, sum_quadvector, scalar_variable("denominator_scalar")),
+                ),
+                scalar_assignment(
+                    "denominator_norm",
+                    study_norm(
+                        scalar_variable("denominator_scalar"),
+                        scalar_variable("denominator_quadvector"),
+                        quadvector_square,
+                    ),
+                ),
+                // (a + bJ)⁻¹ = (a - bJ) / (a² - b²J²)
+                scalar_assignment(
+                    "factor_scalar",
+                    scalar_operation(
+                        ExpressionContent::Divide,
+                        scalar_variable("denominator_scalar"),
+                        scalar_variable("denominator_norm"),
+                    ),
+                ),
+                scalar_assignment(
+                    "factor_quadvector",
+                    scalar_operation(
+                        ExpressionContent::Divide,
+                        scalar_operation(ExpressionContent::Subtract, scalar_constant(0), scalar_variable("denominator_quadvector")),
+                        scalar_variable("denominator_norm"),
+                    ),
+                ),
+            ];
+            let factor = versor_class.study_number(scalar_variable("factor_scalar"), scalar_variable("factor_quadvector"), quadvector_index);
+            (body, invocation(geometric_product, variable("sum"), vec![factor]))
+        } else {
+            let body = vec![
+                scalar_assignment(
+                    "magnitude",
+                    Expression {
+                        size: 1,
+                        content: ExpressionContent::Access(Box::new(invocation(magnitude, variable(parameter_a.name), vec![])), 0),
+                    },
+                ),
+                sum(invocation(
+                    scalar_addition,
+                    variable(parameter_a.name),
+                    vec![scalar_variable("magnitude")],
+                )),
+            ];
+            let factor = scalar_operation(
+                ExpressionContent::Divide,
+                scalar_constant(1),
+                Expression {
+                    size: 1,
+                    content: ExpressionContent::SquareRoot(Box::new(scalar_operation(
+                        ExpressionContent::Multiply,
+                        versor_class.scalar_part(variable("sum")),
+                        scalar_constant(2),
+                    ))),
+                },
+            );
+            (body, invocation(scale, variable("sum"), vec![factor]))
+        };
+        body.push(AstNode::ReturnStatement {
+            expression: Box::new(result),
+        });
+        AstNode::TraitImplementation {
+            result: Parameter {
+                name,
+                data_type: parameter_a.data_type.clone(),
+            },
+            parameters: vec![parameter_a.clone()],
+            body,
+        }
+    }
+
+    /// Scalar element of an `expression` of this class
+    fn scalar_part<'a>(&self, expression: Expression<'a>) -> Expression<'a> {
+        self.element_part(expression, 0)
+    }
+
+    /// Element `index` of an `expression` of this class, as oriented in this class
+    fn element_part<'a>(&self, expression: Expression<'a>, index: BasisElementIndex) -> Expression<'a> {
+        let position = self.flat_basis().iter().position(|element| element.index == index).unwrap();
+        let (group_index, index_in_group) = self.index_in_group(position);
+        Expression {
+            size: 1,
+            content: ExpressionContent::Gather(
+                Box::new(Expression {
+                    size: self.grouped_basis[group_index].len(),
+                    content: expression.content,
+                }),
+                vec![(group_index, index_in_group)],
+            ),
+        }
+    }
+
+    /// Element of this class whose scalar part is the scalar variable `scalar` and whose element `quadvector_index`,
+    /// as oriented in this class, is the scalar variable `quadvector`
+    fn study_number<'a>(&'a self, scalar: Expression<'a>, quadvector: Expression<'a>, quadvector_index: BasisElementIndex) -> Expression<'a> {
+        let groups = self
+            .grouped_basis
+            .iter()
+            .map(|group| {
+                let size = group.len();
+                let mut expression: Option<Expression> = None;
+                for (value, index) in [(scalar.clone(), 0), (quadvector.clone(), quadvector_index)] {
+                    let Some(index_in_group) = group.iter().position(|element| element.index == index) else {
+                        continue;
+                    };
+                    if size == 1 {
+                        expression = Some(value);
+                        continue;
+                    }
+                    let term = Expression {
+                        size,
+                        content: ExpressionContent::Multiply(
+                            Box::new(Expression {
+                                size,
+                                content: ExpressionContent::Gather(Box::new(value), vec![(0, 0)]),
+                            }),
+                            Box::new(Expression {
+                                size,
+                                content: ExpressionContent::Constant(
+                                    DataType::SimdVector(size),
+                                    (0..size).map(|i| (i == index_in_group) as isize).collect(),
+                                ),
+                            }),
+                        ),
+                    };
+                    expression = Some(match expression {
+                        Some(expression) => Expression {
+                            size,
+                            content: ExpressionContent::Add(Box::new(expression), Box::new(term)),
+                        },
+                        None => term,
+                    });
+                }
+                let expression = expression.unwrap_or(Expression {
+                    size,
+                    content: ExpressionContent::Constant(DataType::SimdVector(size), vec![0; size]),
+                });
+                (DataType::SimdVector(size), *simplify_and_legalize(Box::new(expression)))
+            })
+            .collect();
+        Expression {
+            size: 1,
+            content: ExpressionContent::InvokeClassMethod(self, "Constructor", groups),
+        }
+    }
+
+    /// Declares `square` as the square of the variable `bivector` of the class `versor_class`, `trace` as its scalar part
+    /// and `square_1` ≥ `square_2` as the squares of the commuting simple parts of `bivector`, whose difference is twice `root`
+    fn push_bivector_squares<'a>(
+        body: &mut Vec<AstNode<'a>>,
+        versor_class: &'a MultiVectorClass,
+        data_type: &DataType<'a>,
+        geometric_product: &AstNode<'a>,
+        bivector: &'static str,
+    ) {
+        let variable = |name| Expression {
+            size: 1,
+            content: ExpressionContent::Variable(data_type.clone(), name),
+        };
+        body.push(AstNode::VariableAssignment {
+            name: "square",
+            data_type: Some(data_type.clone()),
+            expression: Box::new(invocation(geometric_product, variable(bivector), vec![variable(bivector)])),
+        });
+        body.push(scalar_assignment("trace", versor_class.scalar_part(variable("square"))));
+        body.push(scalar_assignment(
+            "discriminant",
+            scalar_operation(
+                ExpressionContent::Divide,
+                scalar_operation(
+                    ExpressionContent::Subtract,
+                    scalar_operation(
+                        ExpressionContent::Multiply,
+                        scalar_operation(ExpressionContent::Multiply, scalar_variable("trace"), scalar_variable("trace")),
+                        scalar_constant(2),
+                    ),
+                    versor_class.scalar_part(invocation(geometric_product, variable("square"), vec![variable("square")])),
+                ),
+                scalar_constant(4),
+            ),
+        ));
+        // Rounding can make the discriminant of equal squares negative
+        body.push(scalar_assignment(
+            "root",
+            Expression {
+                size: 1,
+                content: ExpressionContent::Select(
+                    Box::new(scalar_operation(
+                        ExpressionContent::LessThan,
+                        scalar_variable("discriminant"),
+                        scalar_constant(0),
+                    )),
+                    Box::new(scalar_constant(0)),
+                    Box::new(Expression {
+                        size: 1,
+                        content: ExpressionContent::SquareRoot(Box::new(scalar_variable("discriminant"))),
+                    }),
+                ),
+            },
+        ));
+        for (name, operation) in [
+            ("square_1", ExpressionContent::Add as fn(_, _) -> _),
+            ("square_2", ExpressionContent::Subtract),
+        ] {
+            body.push(scalar_assignment(
+                name,
+                scalar_operation(
+                    operation,
+                    scalar_operation(ExpressionContent::Divide, scalar_variable("trace"), scalar_constant(2)),
+                    scalar_variable("root"),
+                ),
+            ));
+        }
+    }
+}
+
+/// Invokes the trait implementation `ast_node` on `receiver`, passing the `arguments` as its remaining parameters
+fn invocation<'a>(ast_node: &AstNode<'a>, receiver: Expression<'a>, arguments: Vec<Expression<'a>>) -> Expression<'a> {
+    match ast_node {
+        AstNode::TraitImplementation { result, parameters, .. } => Expression {
+            size: 1,
+            content: ExpressionContent::InvokeInstanceMethod(
+                parameters[0].data_type.clone(),
+                Box::new(receiver),
+                result.name,
+                result.data_type.clone(),
+                parameters[1..]
+                    .iter()
+                    .map(|parameter| parameter.data_type.clone())
+                    .zip(arguments)
+                    .collect(),
+            ),
+        },
+        _ => unreachable!(),
+    }
+}
+
+fn scalar_variable<'a>(name: &'static str) -> Expression<'a> {
+    Expression {
+        size: 1,
+        content: ExpressionContent::Variable(DataType::SimdVector(1), name),
+    }
+}
+
+fn scalar_constant<'a>(value: isize) -> Expression<'a> {
+    Expression {
+        size: 1,
+        content: ExpressionContent::Constant(DataType::SimdVector(1), vec![value]),
+    }
+}
+
+fn scalar_operation<'a>(
+    operation: fn(Box<Expression<'a>>, Box<Expression<'a>>) -> ExpressionContent<'a>,
+    lhs: Expression<'a>,
+    rhs: Expression<'a>,
+) -> Expression<'a> {
+    Expression {
+        size: 1,
+        content: operation(Box::new(lhs), Box::new(rhs)),
+    }
+}
+
+fn scalar_function<'a>(function: ScalarFunction, expression: Expression<'a>) -> Expression<'a> {
+    Expression {
+        size: 1,
+        content: ExpressionContent::Function(function, Box::new(expression)),
+    }
+}
+
+fn scalar_assignment<'a>(name: &'static str, expression: Expression<'a>) -> AstNode<'a> {
+    AstNode::VariableAssignment {
+        name,
+        data_type: Some(DataType::SimdVector(1)),
+        expression: Box::new(expression),
+    }
+}
+
+/// Scalar part of the principal square root of the study number `scalar` + `quadvector` J, given the square of J
+fn study_square_root<'a>(scalar: Expression<'a>, quadvector: Expression<'a>, quadvector_square: isize) -> Expression<'a> {
+    let square_root = |expression| Expression {
+        size: 1,
+        content: ExpressionContent::SquareRoot(Box::new(expression)),
+    };
+    if quadvector_square == 0 {
+        return square_root(scalar);
+    }
+    // √(a + bJ) = x + yJ with x² = (a + √(a² - b²J²)) / 2 and y = b / 2x
+    square_root(scalar_operation(
+        ExpressionContent::Divide,
+        scalar_operation(
+            ExpressionContent::Add,
+            scalar.clone(),
+            square_root(study_norm(scalar.clone(), quadvector, quadvector_square)),
+        ),
+        scalar_constant(2),
+    ))
+}
+
+/// The product of the study number `scalar` + `quadvector` J and its conjugate, given the square of J
+fn study_norm<'a>(scalar: Expression<'a>, quadvector: Expression<'a>, quadvector_square: isize) -> Expression<'a> {
+    let scalar_square = scalar_operation(ExpressionContent::Multiply, scalar.clone(), scalar);
+    if quadvector_square == 0 {
+        return scalar_square;
+    }
+    let mut quadvector_square_term = scalar_operation(ExpressionContent::Multiply, quadvector.clone(), quadvector);
+    if quadvector_square.abs() != 1 {
+        quadvector_square_term = scalar_operation(
+            ExpressionContent::Multiply,
+            quadvector_square_term,
+            scalar_constant(quadvector_square.abs()),
+        );
+    }
+    scalar_operation(
+        if quadvector_square > 0 {
+            ExpressionContent::Subtract
+        } else {
+            ExpressionContent::Add
+        },
+        scalar_square,
+        quadvector_square_term,
+    )
+}
+
+fn select_if_zero<'a>(value: Expression<'a>, then_expression: Expression<'a>, else_expression: Expression<'a>) -> Expression<'a> {
+    Expression {
+        size: 1,
+        content: ExpressionContent::Select(
+            Box::new(scalar_operation(ExpressionContent::Equal, value, scalar_constant(0))),
+            Box::new(then_expression),
+            Box::new(else_expression),
+        ),
+    }
+}
+
+/// Selects by the sign of `square`, passing the square root of its absolute value to `negative` and `positive`
+fn select_by_sign<'a>(
+    square: Expression<'a>,
+    negative: impl Fn(Expression<'a>) -> Expression<'a>,
+    positive: impl Fn(Expression<'a>) -> Expression<'a>,
+    zero: Expression<'a>,
+) -> Expression<'a> {
+    let square_root = |expression| Expression {
+        size: 1,
+        content: ExpressionContent::SquareRoot(Box::new(expression)),
+    };
+    Expression {
+        size: 1,
+        content: ExpressionContent::Select(
+            Box::new(scalar_operation(ExpressionContent::LessThan, square.clone(), scalar_constant(0))),
+            Box::new(negative(square_root(scalar_operation(
+                ExpressionContent::Subtract,
+                scalar_constant(0),
+                square.clone(),
+            )))),
+            Box::new(Expression {
+                size: 1,
+                content: ExpressionContent::Select(
+                    Box::new(scalar_operation(ExpressionContent::LessThan, scalar_constant(0), square.clone())),
+                    Box::new(positive(square_root(square))),
+                    Box::new(zero),
+                ),
+            }),
+        ),
+    }
+}
+
+/// Scalar part of the exponential of a simple bivector with the given `square`
+fn generalized_cosine(square: Expression) -> Expression {
+    select_by_sign(
+        square,
+        |angle| scalar_function(ScalarFunction::Cosine, angle),
+        |angle| scalar_function(ScalarFunction::HyperbolicCosine, angle),
+        scalar_constant(1),
+    )
+}
+
+/// Factor of the bivector in the exponential of a simple bivector with the given `square`
+fn generalized_sinc(square: Expression) -> Expression {
+    select_by_sign(
+        square,
+        |angle| scalar_operation(ExpressionContent::Divide, scalar_function(ScalarFunction::Sine, angle.clone()), angle),
+        |angle| {
+            scalar_operation(
+                ExpressionContent::Divide,
+                scalar_function(ScalarFunction::HyperbolicSine, angle.clone()),
+                angle,
+            )
+        },
+        scalar_constant(1),
+    )
+}
+
+/// Factor turning the bivector part of cosine + sinc B, scaled by a positive factor, back into the bivector B,
+/// given the likewise scaled `cosine` and the `square` of the scaled bivector part
+fn inverse_generalized_sinc<'a>(cosine: Expression<'a>, square: Expression<'a>) -> Expression<'a> {
+    select_by_sign(
+        square,
+        |sine| {
+            scalar_operation(
+                ExpressionContent::Divide,
+                Expression {
+                    size: 1,
+                    content: ExpressionContent::ArcTangent2(Box::new(sine.clone()), Box::new(cosine.clone())),
+                },
+                sine,
+            )
+        },
+        |sine| {
+            scalar_operation(
+                ExpressionContent::Divide,
+                scalar_function(
+                    ScalarFunction::HyperbolicArcTangent,
+                    scalar_operation(ExpressionContent::Divide, sine.clone(), cosine.clone()),
+                ),
+                sine,
+            )
+        },
+        scalar_operation(ExpressionContent::Divide, scalar_constant(1), cosine.clone()),
+    )
 }
@@ -0,0 +1,202 @@
+use crate::{
+    ast::{AstNode, DataType},
+    dsl::AlgebraDescriptor,
+    emit::element_label,
+};
+
+/// Trait, parameter types and result type of a single trait implementation
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TraitImplementationSignature {
+    pub trait_name: String,
+    pub parameters: Vec<String>,
+    pub result: String,
+}
+
+/// Collects which traits are implemented for which classes while an algebra is emitted
+///
+/// Set `Emitter::trait_coverage` to record all emitted classes and trait implementations.
+#[derive(Clone, Debug)]
+pub struct TraitCoverage {
+    pub algebra_name: String,
+    pub classes: Vec<(String, Vec<String>)>,
+    pub implementations: Vec<TraitImplementationSignature>,
+    involution_names: Vec<&'static str>,
+    product_names: Vec<&'static str>,
+}
+
+const CATEGORIES: &[(&str, &str)] = &[
+    ("basic", "Constants, conversions and element wise operations"),
+    ("involution", "Involutions"),
+    ("product", "Products"),
+    ("derived", "Derived traits"),
+];
+
+fn type_name(data_type: &DataType) -> String {
+    match data_type {
+        DataType::Integer => "isize".to_string(),
+        DataType::GradeMask => "u128".to_string(),
+        DataType::SimdVector(1) => "f32".to_string(),
+        DataType::SimdVector(size) => format!("Simd32x{}", size),
+        DataType::MultiVector(class) => class.class_name.clone(),
+    }
+}
+
+impl TraitCoverage {
+    pub fn new(algebra_descriptor: &AlgebraDescriptor) -> Self {
+        Self {
+            algebra_name: algebra_descriptor.name.clone(),
+            classes: Vec::new(),
+            implementations: Vec::new(),
+            involution_names: algebra_descriptor.involutions(&[]).into_iter().map(|(name, _)| name).collect(),
+            product_names: algebra_descriptor.products(&[], &[]).into_iter().map(|(name, _)| name).collect(),
+        }
+    }
+
+    pub fn record(&mut self, ast_node: &AstNode) {
+        match ast_node {
+            AstNode::ClassDefinition { class, generator_names } => {
+                let elements = class.flat_basis().iter().map(|element| element_label(element, generator_names)).collect();
+                self.classes.push((class.class_name.clone(), elements));
+            }
+            AstNode::TraitImplementation { result, parameters, .. } => {
+                self.implementations.push(TraitImplementationSignature {
+                    trait_name: result.name.to_string(),
+                    parameters: parameters.iter().map(|parameter| type_name(&parameter.data_type)).collect(),
+                    result: type_name(&result.data_type),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    /// One of the keys in `CATEGORIES`
+    pub fn category(&self, implementation: &TraitImplementationSignature) -> &'static str {
+        let trait_name = implementation.trait_name.as_str();
+        if self.involution_names.contains(&trait_name) {
+            "involution"
+        } else if self.product_names.contains(&trait_name) {
+            "product"
+        } else if matches!(trait_name, "Zero" | "One" | "Into" | "Add" | "Sub" | "Div")
+            || (trait_name == "Mul" && implementation.parameters[0] == implementation.parameters[1])
+        {
+            "basic"
+        } else {
+            "derived"
+        }
+    }
+
+    pub fn write_json<W: std::io::Write>(&self, collector: &mut W) -> std::io::Result<()> {
+        let quoted_list = |names: &[String]| names.iter().map(|name| format!("{:?}", name)).collect::<Vec<_>>().join(", ");
+        collector.write_fmt(format_args!("{{\n    \"algebra\": {:?},\n    \"classes\": [\n", self.algebra_name))?;
+        for (i, (class_name, elements)) in self.classes.iter().enumerate() {
+            collector.write_fmt(format_args!(
+                "        {{\"name\": {:?}, \"elements\": [{}]}}{}\n",
+                class_name,
+                quoted_list(elements),
+                if i + 1 < self.classes.len() { "," } else { "" }
+            ))?;
+        }
+        collector.write_all(b"    ],\n    \"implementations\": [\n")?;
+        for (i, implementation) in self.implementations.iter().enumerate() {
+            collector.write_fmt(format_args!(
+                "        {{\"trait\": {:?}, \"category\": {:?}, \"parameters\": [{}], \"result\": {:?}}}{}\n",
+                implementation.trait_name,
+                self.category(implementation),
+                quoted_list(&implementation.parameters),
+                implementation.result,
+                if i + 1 < self.implementations.len() { "," } else { "" }
+            ))?;
+        }
+        collector.write_all(b"    ]\n}\n")
+    }
+
+    /// Writes a table of the single class traits and a class by class matrix for each trait of two parameters
+    pub fn write_markdown<W: std::io::Write>(&self, collector: &mut W) -> std::io::Result<()> {
+        let class_names = self.classes.iter().map(|(class_name, _)| class_name.clone()).collect::<Vec<_>>();
+        collector.write_fmt(format_args!(
+            "# Trait coverage of {}\n\n## Classes\n\n| Class | Elements |\n|---|---|\n",
+            self.algebra_name
+        ))?;
+        for (class_name, elements) in self.classes.iter() {
+            collector.write_fmt(format_args!("| {} | {} |\n", class_name, elements.join(", ")))?;
+        }
+        for (category, title) in CATEGORIES.iter() {
+            let implementations = self
+                .implementations
+                .iter()
+                .filter(|implementation| self.category(implementation) == *category)
+                .collect::<Vec<_>>();
+            if implementations.is_empty() {
+                continue;
+            }
+            collector.write_fmt(format_args!("\n## {}\n", title))?;
+            // Into has a single parameter, but the result type varies, so it is shown as a matrix too
+            let is_unary = |implementation: &TraitImplementationSignature| implementation.parameters.len() < 2 && implementation.trait_name != "Into";
+            let mut unary_traits: Vec<&str> = Vec::new();
+            let mut binary_traits: Vec<&str> = Vec::new();
+            for implementation in implementations.iter() {
+                let trait_names = if is_unary(implementation) {
+                    &mut unary_traits
+                } else {
+                    &mut binary_traits
+                };
+                if !trait_names.contains(&implementation.trait_name.as_str()) {
+                    trait_names.push(&implementation.trait_name);
+                }
+            }
+            if !unary_traits.is_empty() {
+                collector.write_fmt(format_args!(
+                    "\n| Class | {} |\n|---|{}\n",
+                    unary_traits.join(" | "),
+                    "---|".repeat(unary_traits.len())
+                ))?;
+                for class_name in class_names.iter() {
+                    collector.write_fmt(format_args!("| {} |", class_name))?;
+                    for trait_name in unary_traits.iter() {
+                        let result = implementations.iter().find(|implementation| {
+                            is_unary(implementation)
+                                && implementation.trait_name == *trait_name
+                                && implementation.parameters.first().unwrap_or(&implementation.result) == class_name
+                        });
+                        collector.write_fmt(format_args!(
+                            " {} |",
+                            result.map(|implementation| implementation.result.as_str()).unwrap_or("")
+                        ))?;
+                    }
+                    collector.write_all(b"\n")?;
+                }
+            }
+            for trait_name in binary_traits.iter() {
+                let operands = implementations
+                    .iter()
+                    .filter(|implementation| !is_unary(implementation) && implementation.trait_name == *trait_name)
+                    .map(|implementation| {
+                        let other = implementation.parameters.get(1).unwrap_or(&implementation.result);
+                        (implementation.parameters[0].as_str(), other.as_str(), implementation.result.as_str())
+                    })
+                    .collect::<Vec<_>>();
+                let mut columns = class_names.iter().map(|class_name| class_name.as_str()).collect::<Vec<_>>();
+                for (_, other, _) in operands.iter() {
+                    if !columns.contains(other) {
+                        columns.push(other);
+                    }
+                }
+                collector.write_fmt(format_args!(
+                    "\n### {}\n\n| | {} |\n|---|{}\n",
+                    trait_name,
+                    columns.join(" | "),
+                    "---|".repeat(columns.len())
+                ))?;
+                for class_name in class_names.iter() {
+                    collector.write_fmt(format_args!("| {} |", class_name))?;
+                    for column in columns.iter() {
+                        let result = operands.iter().find(|(first, other, _)| first == class_name && other == column);
+                        collector.write_fmt(format_args!(" {} |", result.map(|(_, _, result)| *result).unwrap_or("")))?;
+                    }
+                    collector.write_all(b"\n")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
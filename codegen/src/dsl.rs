@@ -0,0 +1,997 @@
+use crate::{
+    algebra::{
+        grade_projection_name, static_name, BasisElement, BasisElementIndex, GeometricAlgebra, Involution, MultiVectorClass,
+        MultiVectorClassRegistry, Product,
+    },
+    ast::AstNode,
+    emit::element_accessor_name,
+    grade_filter::GradeFilter,
+};
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub class_name: Option<String>,
+    pub message: String,
+    pub suggestion: Option<String>,
+}
+
+impl ParseError {
+    pub fn new(column: usize, message: String) -> Self {
+        Self {
+            line: 1,
+            column,
+            class_name: None,
+            message,
+            suggestion: None,
+        }
+    }
+
+    pub fn with_suggestion(mut self, suggestion: String) -> Self {
+        self.suggestion = Some(suggestion);
+        self
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_fmt(format_args!("{}:{}: ", self.line, self.column))?;
+        if let Some(class_name) = &self.class_name {
+            formatter.write_fmt(format_args!("in class `{}`: ", class_name))?;
+        }
+        formatter.write_str(&self.message)?;
+        if let Some(suggestion) = &self.suggestion {
+            formatter.write_fmt(format_args!(" ({})", suggestion))?;
+        }
+        Ok(())
+    }
+}
+
+/// Trims whitespace, keeping track of the column the text starts at
+fn trimmed(text: &str, column: usize) -> (usize, &str) {
+    let trimmed = text.trim_start();
+    (column + text.len() - trimmed.len(), trimmed.trim_end())
+}
+
+/// Splits at `separator` and trims the parts, keeping track of the column each part starts at
+fn split_trimmed(text: &str, column: usize, separator: char) -> Vec<(usize, &str)> {
+    let mut result = Vec::new();
+    let mut offset = 0;
+    for part in text.split(separator) {
+        result.push(trimmed(part, column + offset));
+        offset += part.len() + separator.len_utf8();
+    }
+    result
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_') && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate", "do", "dyn", "else", "enum", "extern", "false",
+    "final", "fn", "for", "if", "impl", "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Names which the generated classes already use for other methods
+fn is_reserved_accessor_name(name: &str) -> bool {
+    if RUST_KEYWORDS.contains(&name) || name == "new" || name == "from_groups" {
+        return true;
+    }
+    match name.strip_prefix("group") {
+        Some(suffix) => {
+            let digits = suffix.strip_suffix("_mut").unwrap_or(suffix);
+            !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+        }
+        None => false,
+    }
+}
+
+/// A product of two classes which is not generated, because no class has the basis elements of its result
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MissingProduct {
+    pub class_a: String,
+    pub class_b: String,
+    pub product: &'static str,
+    pub signature: Vec<BasisElementIndex>,
+}
+
+/// Restricts the traits emitted for a class, or for a pair of classes, e.g. `[Motor, Point]: Transformation`
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TraitSelection {
+    pub class_a: String,
+    pub class_b: Option<String>,
+    pub traits: Vec<String>,
+}
+
+/// An involution declared in the descriptor, which negates the elements of the given grades, e.g. `involution GradeInvolution: 1, 3`
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct GradeNegation {
+    pub name: &'static str,
+    pub grades: Vec<usize>,
+}
+
+/// A product declared in the descriptor, which keeps the terms of the geometric product passing the grade filter, e.g. `product FatDot: t == |r-s|`
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct FilteredProduct {
+    pub name: &'static str,
+    pub grade_filter: GradeFilter,
+}
+
+pub struct AlgebraDescriptor {
+    pub name: String,
+    pub generator_squares: Vec<isize>,
+    pub generator_names: Vec<String>,
+    /// Off-diagonal entries of the metric, see `GeometricAlgebra::generator_inner_products`
+    pub generator_inner_products: Vec<(usize, usize, isize)>,
+    pub classes: Vec<MultiVectorClass>,
+    pub trait_selections: Vec<TraitSelection>,
+    /// Traits of `OPTIONAL_TRAITS` emitted for all classes, e.g. `enable CommutatorProduct`
+    pub enabled_traits: Vec<String>,
+    pub grade_negations: Vec<GradeNegation>,
+    pub filtered_products: Vec<FilteredProduct>,
+}
+
+impl AlgebraDescriptor {
+    /// Parses the single argument syntax: `name:squares;Class:e1,e2|e3;...`
+    ///
+    /// Generators can be named by writing `name=square` instead of only the square, e.g. `w=0,x=1,y=1,z=1`.
+    /// Basis elements can then also be written as products of these names, e.g. `xy`, or `no_ni` for names longer than one character.
+    /// A non-diagonal metric is declared by inner products of pairs of generators, e.g. `e1=1,e2=1,no=0,ni=0,no.ni=-1`.
+    /// Basis elements are then outer products of the generators.
+    ///
+    /// Elements of a class can be given an alias by writing `alias=element`, e.g. `Point: e123, x=e032, y=e013, z=e021`.
+    ///
+    /// `involution GradeInvolution: 1, 3` declares an involution which negates the elements of the listed grades.
+    /// `product FatDot: t == |r-s|` declares a product, see `GradeFilter` for the syntax of the condition.
+    /// The method names of both are the trait names in snake case, e.g. `fat_dot`.
+    ///
+    /// `[Motor]: GeometricProduct, Transformation` restricts the traits emitted with `Motor` as first parameter,
+    /// `[Motor, Point]: Transformation` does the same for a pair of classes and takes precedence.
+    /// Traits the selected ones depend on are emitted as well.
+    /// `enable CommutatorProduct, GeometricAntiProduct` emits traits of `OPTIONAL_TRAITS` for all classes.
+    pub fn parse_inline(config: &str) -> Result<Self, Vec<ParseError>> {
+        let mut errors = Vec::new();
+        let mut config_iter = split_trimmed(config, 1, ';').into_iter();
+        let (column, header) = config_iter.next().unwrap();
+        let mut result = Self::parse_header(header, column)?;
+        for (column, definition) in config_iter {
+            if let Err(definition_errors) = result.parse_definition(definition, column) {
+                errors.extend(definition_errors);
+            }
+        }
+        if errors.is_empty() {
+            Ok(result)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Parses a descriptor file containing any number of algebras
+    ///
+    /// Each algebra starts with a line `algebra name: squares`,
+    /// followed by one line `Class: e1,e2|e3` per class,
+    /// optionally lines `involution Name: grades` and `product Name: condition` declaring traits
+    /// and optionally lines `[Class]: Trait, Trait` selecting traits.
+    /// Everything after a `#` is a comment.
+    pub fn parse_file(source: &str) -> Result<Vec<Self>, Vec<ParseError>> {
+        let mut errors = Vec::new();
+        let mut result: Vec<Self> = Vec::new();
+        let mut skip_algebra = false;
+        for (line_index, line) in source.lines().enumerate() {
+            let (column, line) = trimmed(line.split('#').next().unwrap(), 1);
+            if line.is_empty() {
+                continue;
+            }
+            let outcome = if let Some(header) = line.strip_prefix("algebra ") {
+                let (header_column, header) = trimmed(header, column + "algebra ".len());
+                skip_algebra = false;
+                Self::parse_header(header, header_column)
+                    .map(|algebra| result.push(algebra))
+                    .inspect_err(|_| skip_algebra = true)
+            } else if skip_algebra {
+                Ok(())
+            } else if let Some(algebra) = result.last_mut() {
+                algebra.parse_definition(line, column)
+            } else {
+                Err(vec![ParseError::new(column, "class defined outside of an algebra".to_string())
+                    .with_suggestion("start with a line `algebra name: generator squares`".to_string())])
+            };
+            if let Err(line_errors) = outcome {
+                errors.extend(line_errors.into_iter().map(|mut error| {
+                    error.line = line_index + 1;
+                    error
+                }));
+            }
+        }
+        if errors.is_empty() {
+            Ok(result)
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn parse_header(header: &str, column: usize) -> Result<Self, Vec<ParseError>> {
+        let header_iter = split_trimmed(header, column, ':');
+        if header_iter.len() != 2 {
+            return Err(vec![ParseError::new(column, format!("expected algebra descriptor, found `{}`", header))
+                .with_suggestion("write `name: generator squares`, e.g. `ppga3d: 0, 1, 1, 1`".to_string())]);
+        }
+        let mut errors = Vec::new();
+        let (column, name) = header_iter[0];
+        if !is_identifier(name) {
+            errors.push(
+                ParseError::new(column, format!("`{}` is not a valid algebra name", name))
+                    .with_suggestion("use letters, digits and underscores only".to_string()),
+            );
+        }
+        let mut generator_squares = Vec::new();
+        let mut generator_names = Vec::new();
+        let mut inner_product_entries = Vec::new();
+        for (column, generator) in split_trimmed(header_iter[1].1, header_iter[1].0, ',') {
+            if let Some((generator_pair, value)) = generator.split_once('=').filter(|(generator_pair, _)| generator_pair.contains('.')) {
+                let value_column = column + generator.len() - value.len();
+                inner_product_entries.push((split_trimmed(generator_pair, column, '.'), trimmed(value, value_column)));
+                continue;
+            }
+            let (column, square) = if let Some((generator_name, square)) = generator.split_once('=') {
+                let (name_column, generator_name) = trimmed(generator_name, column);
+                if !matches!(generator_name.chars().next(), Some(c) if c.is_ascii_alphabetic())
+                    || !generator_name.chars().all(|c| c.is_ascii_alphanumeric())
+                    || generator_name == "scalar"
+                {
+                    errors.push(
+                        ParseError::new(name_column, format!("`{}` is not a valid generator name", generator_name))
+                            .with_suggestion("use letters and digits only, starting with a letter".to_string()),
+                    );
+                } else if generator_names.iter().any(|name| name == generator_name) {
+                    errors.push(ParseError::new(name_column, format!("generator name `{}` is used twice", generator_name)));
+                }
+                generator_names.push(generator_name.to_string());
+                trimmed(square, column + generator.len() - square.len())
+            } else {
+                (column, generator)
+            };
+            match square.parse::<isize>() {
+                Ok(square) => generator_squares.push(square),
+                Err(_) => {
+                    generator_squares.push(0);
+                    errors.push(
+                        ParseError::new(column, format!("generator square `{}` is not an integer", square))
+                            .with_suggestion("use -1, 0 or 1".to_string()),
+                    );
+                }
+            }
+        }
+        if !generator_names.is_empty() && generator_names.len() != generator_squares.len() {
+            errors.push(
+                ParseError::new(header_iter[1].0, "either all or none of the generators need to be named".to_string())
+                    .with_suggestion("write `name=square` for every generator, e.g. `w=0, x=1, y=1, z=1`".to_string()),
+            );
+        }
+        let max_generators = std::mem::size_of::<BasisElementIndex>() * 8;
+        if generator_squares.len() > max_generators {
+            errors.push(ParseError::new(
+                header_iter[1].0,
+                format!("{} generators exceed the maximum of {}", generator_squares.len(), max_generators),
+            ));
+        }
+        // The inner products refer to the generators by name, so they are only checked once the generators are valid
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        // Single hexadecimal digits only index 16 generators, beyond that they are named `e0`, `e1`, ... and joined by underscores, e.g. `e3_e17`
+        if generator_names.is_empty() && generator_squares.len() > 16 {
+            generator_names = (0..generator_squares.len()).map(|index| format!("e{}", index)).collect();
+        }
+        let mut generator_inner_products: Vec<(usize, usize, isize)> = Vec::new();
+        for (generator_pair, (value_column, value)) in inner_product_entries {
+            let algebra = GeometricAlgebra {
+                generator_squares: &generator_squares,
+                generator_names: &generator_names,
+                generator_inner_products: &[],
+            };
+            let mut generator_indices = Vec::new();
+            for (generator_column, generator) in generator_pair.iter() {
+                match BasisElement::parse(generator, &algebra) {
+                    Ok(element) if element.grade() == 1 && element.scalar == 1 => generator_indices.push(element.index.trailing_zeros() as usize),
+                    Ok(_) => errors.push(ParseError::new(*generator_column, format!("`{}` is not a generator", generator))),
+                    Err(mut error) => {
+                        error.column += generator_column;
+                        errors.push(error);
+                    }
+                }
+            }
+            if generator_indices.len() != generator_pair.len() {
+                continue;
+            }
+            if generator_indices.len() != 2 || generator_indices[0] == generator_indices[1] {
+                errors.push(
+                    ParseError::new(generator_pair[0].0, "expected two different generators".to_string())
+                        .with_suggestion("write `a.b=value` for the inner product of the generators `a` and `b`".to_string()),
+                );
+                continue;
+            }
+            let (i, j) = (
+                generator_indices[0].min(generator_indices[1]),
+                generator_indices[0].max(generator_indices[1]),
+            );
+            if generator_inner_products
+                .iter()
+                .any(|(other_i, other_j, _)| (*other_i, *other_j) == (i, j))
+            {
+                errors.push(ParseError::new(generator_pair[0].0, "inner product is defined twice".to_string()));
+                continue;
+            }
+            match value.parse::<isize>() {
+                Ok(value) => generator_inner_products.push((i, j, value)),
+                Err(_) => errors.push(ParseError::new(value_column, format!("inner product `{}` is not an integer", value))),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        generator_inner_products.sort_unstable();
+        Ok(Self {
+            name: name.to_owned(),
+            generator_squares,
+            generator_names,
+            generator_inner_products,
+            classes: Vec::new(),
+            trait_selections: Vec::new(),
+            enabled_traits: Vec::new(),
+            grade_negations: Vec::new(),
+            filtered_products: Vec::new(),
+        })
+    }
+
+    fn parse_definition(&mut self, definition: &str, column: usize) -> Result<(), Vec<ParseError>> {
+        if definition.starts_with('[') {
+            self.parse_trait_selection(definition, column).map_err(|error| vec![error])
+        } else if let Some(enabled_traits) = definition.strip_prefix("enable ") {
+            self.parse_enabled_traits(enabled_traits, column + "enable ".len())
+                .map_err(|error| vec![error])
+        } else if let Some(grade_negation) = definition.strip_prefix("involution ") {
+            self.parse_grade_negation(grade_negation, column + "involution ".len())
+                .map_err(|error| vec![error])
+        } else if let Some(filtered_product) = definition.strip_prefix("product ") {
+            self.parse_filtered_product(filtered_product, column + "product ".len())
+                .map_err(|error| vec![error])
+        } else {
+            self.parse_class(definition, column)
+        }
+    }
+
+    fn parse_trait_selection(&mut self, selection_descriptor: &str, column: usize) -> Result<(), ParseError> {
+        let selection_descriptor_iter = split_trimmed(selection_descriptor, column, ':');
+        let classes = selection_descriptor_iter[0]
+            .1
+            .strip_prefix('[')
+            .and_then(|classes| classes.strip_suffix(']'));
+        let (classes, traits) = match (classes, selection_descriptor_iter.get(1)) {
+            (Some(classes), Some(traits)) if selection_descriptor_iter.len() == 2 => (classes, traits),
+            _ => {
+                return Err(
+                    ParseError::new(column, format!("expected trait selection, found `{}`", selection_descriptor))
+                        .with_suggestion("write `[Class]: Trait, Trait` or `[Class, Class]: Trait, Trait`".to_string()),
+                )
+            }
+        };
+        let class_names = split_trimmed(classes, column + 1, ',');
+        if class_names.len() > 2 {
+            return Err(ParseError::new(
+                column,
+                "traits can only be selected for a class or a pair of classes".to_string(),
+            ));
+        }
+        for (class_column, class_name) in class_names.iter() {
+            if !self.classes.iter().any(|class| class.class_name == *class_name) {
+                return Err(ParseError::new(*class_column, format!("unknown class `{}`", class_name))
+                    .with_suggestion("define the class before selecting its traits".to_string()));
+            }
+        }
+        let class_a = class_names[0].1.to_string();
+        let class_b = class_names.get(1).map(|(_, class_name)| class_name.to_string());
+        if self
+            .trait_selections
+            .iter()
+            .any(|selection| selection.class_a == class_a && selection.class_b == class_b)
+        {
+            return Err(ParseError::new(column, format!("traits of `{}` are selected twice", classes)));
+        }
+        let known_traits = self.known_traits();
+        let mut selected_traits = Vec::new();
+        // An empty list emits only the traits other classes depend on
+        for (trait_column, trait_name) in split_trimmed(traits.1, traits.0, ',').into_iter().filter(|_| !traits.1.is_empty()) {
+            if !known_traits.contains(&trait_name) {
+                return Err(ParseError::new(trait_column, format!("unknown trait `{}`", trait_name))
+                    .with_suggestion(format!("expected one of {}", known_traits.join(", "))));
+            }
+            selected_traits.push(trait_name.to_string());
+        }
+        self.trait_selections.push(TraitSelection {
+            class_a,
+            class_b,
+            traits: selected_traits,
+        });
+        Ok(())
+    }
+
+    fn parse_enabled_traits(&mut self, enabled_traits: &str, column: usize) -> Result<(), ParseError> {
+        for (trait_column, trait_name) in split_trimmed(enabled_traits, column, ',') {
+            if !crate::OPTIONAL_TRAITS.contains(&trait_name) {
+                return Err(ParseError::new(trait_column, format!("`{}` can not be enabled", trait_name))
+                    .with_suggestion(format!("expected one of {}", crate::OPTIONAL_TRAITS.join(", "))));
+            }
+            if self.enabled_traits.iter().any(|name| name == trait_name) {
+                return Err(ParseError::new(trait_column, format!("`{}` is enabled twice", trait_name)));
+            }
+            self.enabled_traits.push(trait_name.to_string());
+        }
+        Ok(())
+    }
+
+    fn parse_grade_negation(&mut self, grade_negation_descriptor: &str, column: usize) -> Result<(), ParseError> {
+        let (column, grade_negation_descriptor) = trimmed(grade_negation_descriptor, column);
+        let grade_negation_descriptor_iter = split_trimmed(grade_negation_descriptor, column, ':');
+        if grade_negation_descriptor_iter.len() != 2 {
+            return Err(
+                ParseError::new(column, format!("expected involution, found `{}`", grade_negation_descriptor))
+                    .with_suggestion("write `involution Name: grades`, e.g. `involution GradeInvolution: 1, 3`".to_string()),
+            );
+        }
+        let (name_column, name) = grade_negation_descriptor_iter[0];
+        let name = self.parse_trait_name(name, name_column)?;
+        let mut grades = Vec::new();
+        let (grades_column, grades_descriptor) = grade_negation_descriptor_iter[1];
+        for (grade_column, grade) in split_trimmed(grades_descriptor, grades_column, ',')
+            .into_iter()
+            .filter(|_| !grades_descriptor.is_empty())
+        {
+            let grade = grade
+                .parse::<usize>()
+                .ok()
+                .filter(|grade| *grade <= self.generator_squares.len())
+                .ok_or_else(|| {
+                    ParseError::new(grade_column, format!("`{}` is not a grade", grade))
+                        .with_suggestion(format!("use 0 to {}", self.generator_squares.len()))
+                })?;
+            if grades.contains(&grade) {
+                return Err(ParseError::new(grade_column, format!("grade {} is listed twice", grade)));
+            }
+            grades.push(grade);
+        }
+        grades.sort_unstable();
+        self.grade_negations.push(GradeNegation { name, grades });
+        Ok(())
+    }
+
+    /// Checks that `name` can be the name of a new trait
+    fn parse_trait_name(&self, name: &str, column: usize) -> Result<&'static str, ParseError> {
+        if !is_identifier(name) || !name.starts_with(|c: char| c.is_ascii_uppercase()) {
+            return Err(ParseError::new(column, format!("`{}` is not a valid trait name", name))
+                .with_suggestion("use CamelCase, e.g. `GradeInvolution`".to_string()));
+        }
+        if self.known_traits().contains(&name) {
+            return Err(ParseError::new(column, format!("trait `{}` already exists", name)));
+        }
+        Ok(static_name(name.to_string()))
+    }
+
+    fn parse_filtered_product(&mut self, filtered_product_descriptor: &str, column: usize) -> Result<(), ParseError> {
+        let (column, filtered_product_descriptor) = trimmed(filtered_product_descriptor, column);
+        let Some((name, grade_filter)) = filtered_product_descriptor.split_once(':') else {
+            return Err(
+                ParseError::new(column, format!("expected product, found `{}`", filtered_product_descriptor))
+                    .with_suggestion("write `product Name: condition`, e.g. `product FatDot: t == |r-s|`".to_string()),
+            );
+        };
+        let (name_column, name) = trimmed(name, column);
+        let name = self.parse_trait_name(name, name_column)?;
+        let (grade_filter_column, grade_filter) = trimmed(grade_filter, column + name.len() + 1);
+        let grade_filter = GradeFilter::parse(grade_filter, grade_filter_column)?;
+        self.filtered_products.push(FilteredProduct { name, grade_filter });
+        Ok(())
+    }
+
+    /// Traits which can be selected, see `is_trait_selected`
+    fn known_traits(&self) -> Vec<&'static str> {
+        let mut known_traits = self.involutions(&[]).into_iter().map(|(name, _)| name).collect::<Vec<_>>();
+        known_traits.extend(self.products(&[], &[]).into_iter().map(|(name, _)| name));
+        known_traits.extend(crate::GENERATED_TRAITS);
+        known_traits
+    }
+
+    /// The involutions of the algebra, including the grade projections and the involutions declared in the descriptor
+    pub fn involutions(&self, basis: &[BasisElement]) -> Vec<(&'static str, Involution)> {
+        let involution = Involution::identity(basis);
+        let mut involutions = Involution::involutions(basis, &self.algebra());
+        involutions.extend(
+            (0..=self.generator_squares.len())
+                .map(|grade| (grade_projection_name(grade), involution.selected(|element_grade| element_grade == grade))),
+        );
+        involutions.extend(
+            self.grade_negations
+                .iter()
+                .map(|grade_negation| (grade_negation.name, involution.negated(|grade| grade_negation.grades.contains(&grade)))),
+        );
+        involutions
+    }
+
+    /// The products of the algebra, including the products declared in the descriptor
+    pub fn products(&self, a: &[BasisElement], b: &[BasisElement]) -> Vec<(&'static str, Product)> {
+        let algebra = self.algebra();
+        let mut products = Product::products(a, b, &algebra);
+        if !self.filtered_products.is_empty() {
+            let product = Product::new(a, b, &algebra);
+            let dimension = self.generator_squares.len();
+            products.extend(self.filtered_products.iter().map(|filtered_product| {
+                (
+                    filtered_product.name,
+                    product.projected(|r, s, t| filtered_product.grade_filter.evaluate(r, s, t, dimension)),
+                )
+            }));
+        }
+        products
+    }
+
+    /// Traits which the generated code defines itself, because they are not part of the runtime crate
+    pub fn trait_definitions(&self) -> Vec<AstNode<'static>> {
+        let mut trait_definitions = self
+            .grade_negations
+            .iter()
+            .map(|grade_negation| AstNode::TraitDefinition {
+                name: grade_negation.name,
+                documentation: format!(
+                    "Negates the elements of grade {}",
+                    grade_negation.grades.iter().map(|grade| grade.to_string()).collect::<Vec<_>>().join(", ")
+                ),
+                binary: false,
+            })
+            .collect::<Vec<_>>();
+        trait_definitions.extend(self.filtered_products.iter().map(|filtered_product| AstNode::TraitDefinition {
+            name: filtered_product.name,
+            documentation: format!("Geometric product grade filtered by `{}`", filtered_product.grade_filter.source()),
+            binary: true,
+        }));
+        trait_definitions
+    }
+
+    /// Whether `trait_name` is emitted with `class_a` as first and `class_b` as second parameter
+    ///
+    /// Traits of `OPTIONAL_TRAITS` are only emitted if they are enabled or selected for the class.
+    /// Traits invoked by emitted traits are emitted regardless.
+    pub fn is_trait_selected(&self, trait_name: &str, class_a: &str, class_b: Option<&str>) -> bool {
+        let find_selection = |class_b: Option<&str>| {
+            self.trait_selections
+                .iter()
+                .find(|selection| selection.class_a == class_a && selection.class_b.as_deref() == class_b)
+        };
+        match class_b.and_then(|class_b| find_selection(Some(class_b))).or_else(|| find_selection(None)) {
+            Some(selection) => selection.traits.iter().any(|name| name == trait_name),
+            None => !crate::OPTIONAL_TRAITS.contains(&trait_name) || self.enabled_traits.iter().any(|name| name == trait_name),
+        }
+    }
+
+    fn parse_class(&mut self, class_descriptor: &str, column: usize) -> Result<(), Vec<ParseError>> {
+        let class_descriptor_iter = split_trimmed(class_descriptor, column, ':');
+        if class_descriptor_iter.len() != 2 {
+            return Err(vec![ParseError::new(
+                column,
+                format!("expected class descriptor, found `{}`", class_descriptor),
+            )
+            .with_suggestion("write `ClassName: e1, e2 | e3`".to_string())]);
+        }
+        let (name_column, class_name) = class_descriptor_iter[0];
+        let in_class = |mut error: ParseError| {
+            error.class_name = Some(class_name.to_owned());
+            error
+        };
+        if !is_identifier(class_name) {
+            return Err(vec![in_class(
+                ParseError::new(name_column, format!("`{}` is not a valid class name", class_name))
+                    .with_suggestion("use letters, digits and underscores only".to_string()),
+            )]);
+        }
+        if self.classes.iter().any(|class| class.class_name == class_name) {
+            return Err(vec![in_class(ParseError::new(name_column, "class is defined twice".to_string()))]);
+        }
+        let algebra = self.algebra();
+        let mut errors = Vec::new();
+        let mut grouped_basis = Vec::new();
+        let mut element_aliases = std::collections::HashMap::new();
+        let mut indices = std::collections::HashSet::new();
+        let mut accessor_names = std::collections::HashSet::new();
+        let mut claim_accessor_name = |column: usize, name: &str, suggestion: &str| -> Option<ParseError> {
+            if is_reserved_accessor_name(name) {
+                Some(in_class(
+                    ParseError::new(column, format!("accessor `{}` is a reserved name", name)).with_suggestion(suggestion.to_string()),
+                ))
+            } else if !accessor_names.insert(name.to_string()) || !accessor_names.insert(format!("set_{}", name)) {
+                Some(in_class(
+                    ParseError::new(column, format!("accessor `{}` clashes with another accessor", name)).with_suggestion(suggestion.to_string()),
+                ))
+            } else {
+                None
+            }
+        };
+        for (group_column, group_descriptor) in split_trimmed(class_descriptor_iter[1].1, class_descriptor_iter[1].0, '|') {
+            if group_descriptor.is_empty() {
+                errors.push(in_class(ParseError::new(group_column, "empty group".to_string())));
+                continue;
+            }
+            let element_descriptors = split_trimmed(group_descriptor, group_column, ',');
+            // Groups are emitted as SIMD vectors, which have up to four lanes
+            if element_descriptors.len() > 4 {
+                errors.push(in_class(
+                    ParseError::new(
+                        group_column,
+                        format!("group has {} elements, at most 4 are supported", element_descriptors.len()),
+                    )
+                    .with_suggestion("split it into several groups with `|`".to_string()),
+                ));
+            }
+            let mut group = Vec::new();
+            for (element_column, element_descriptor) in element_descriptors {
+                let (alias, (element_column, element_name)) = match element_descriptor.split_once('=') {
+                    Some((alias, element_name)) => (
+                        Some(trimmed(alias, element_column)),
+                        trimmed(element_name, element_column + element_descriptor.len() - element_name.len()),
+                    ),
+                    None => (None, (element_column, element_descriptor)),
+                };
+                match BasisElement::parse(element_name, &algebra) {
+                    Ok(element) => {
+                        if !indices.insert(element.index) {
+                            errors.push(in_class(ParseError::new(
+                                element_column,
+                                format!("basis element `{}` occurs more than once", element_name),
+                            )));
+                        }
+                        errors.extend(claim_accessor_name(
+                            element_column,
+                            &element_accessor_name(&element, &self.generator_names),
+                            "give the element an alias or rename the generators",
+                        ));
+                        if let Some((alias_column, alias)) = alias {
+                            if !is_identifier(alias) {
+                                errors.push(in_class(
+                                    ParseError::new(alias_column, format!("`{}` is not a valid alias", alias))
+                                        .with_suggestion("use letters, digits and underscores only".to_string()),
+                                ));
+                            } else {
+                                errors.extend(claim_accessor_name(alias_column, alias, "choose a different alias"));
+                            }
+                            element_aliases.insert(element.index, alias.to_string());
+                        }
+                        group.push(element);
+                    }
+                    Err(mut error) => {
+                        error.column += element_column;
+                        errors.push(in_class(error));
+                    }
+                }
+            }
+            grouped_basis.push(group);
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        let class = MultiVectorClass {
+            class_name: class_name.to_owned(),
+            grouped_basis,
+            element_aliases,
+        };
+        if let Some(other) = self.classes.iter().find(|other| other.signature() == class.signature()) {
+            return Err(vec![in_class(ParseError::new(
+                class_descriptor_iter[1].0,
+                format!("same basis elements as class `{}`", other.class_name),
+            ))]);
+        }
+        self.classes.push(class);
+        Ok(())
+    }
+
+    pub fn algebra(&self) -> GeometricAlgebra<'_> {
+        GeometricAlgebra {
+            generator_squares: self.generator_squares.as_slice(),
+            generator_names: self.generator_names.as_slice(),
+            generator_inner_products: self.generator_inner_products.as_slice(),
+        }
+    }
+
+    pub fn registry(&self) -> MultiVectorClassRegistry {
+        let mut registry = MultiVectorClassRegistry::default();
+        for class in self.classes.iter() {
+            registry.register(MultiVectorClass {
+                class_name: class.class_name.clone(),
+                grouped_basis: class.grouped_basis.clone(),
+                element_aliases: class.element_aliases.clone(),
+            });
+        }
+        registry
+    }
+
+    /// Lists the products which are dropped, because their result signature matches no class
+    ///
+    /// Products which are zero for all inputs or not selected, see `is_trait_selected`, are not listed.
+    pub fn missing_products(&self) -> Vec<MissingProduct> {
+        let registry = self.registry();
+        let mut result = Vec::new();
+        for class_a in self.classes.iter() {
+            for class_b in self.classes.iter() {
+                for (name, product) in self.products(&class_a.flat_basis(), &class_b.flat_basis()).iter() {
+                    if !self.is_trait_selected(name, &class_a.class_name, Some(&class_b.class_name)) {
+                        continue;
+                    }
+                    let signature = MultiVectorClass::product_signature(product, class_a, class_b);
+                    if !signature.is_empty() && registry.get(&signature).is_none() {
+                        result.push(MissingProduct {
+                            class_a: class_a.class_name.clone(),
+                            class_b: class_b.class_name.clone(),
+                            product: name,
+                            signature,
+                        });
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Whether every element of `class` times its reversal is a scalar, as for versors,
+    /// so that its inverse is the reversal divided by the squared magnitude
+    pub fn has_versor_inverse(&self, class: &MultiVectorClass) -> bool {
+        let algebra = self.algebra();
+        let basis = class.flat_basis();
+        Involution::involutions(&basis, &algebra)
+            .into_iter()
+            .find(|(name, _)| *name == "Reversal")
+            .is_some_and(|(_, reversal)| Product::new(&basis, &basis, &algebra).squares_to_scalar(&reversal))
+    }
+
+    /// Lists the classes which have no inverse, because their elements need not be versors
+    /// and the general inverse has no closed form for this many generators
+    pub fn classes_without_inverse(&self) -> Vec<String> {
+        if self.generator_squares.len() <= crate::MAX_GENERAL_INVERSE_GENERATORS {
+            return Vec::new();
+        }
+        self.classes
+            .iter()
+            .filter(|class| !self.has_versor_inverse(class))
+            .map(|class| class.class_name.clone())
+            .collect()
+    }
+
+    /// Adds a class for the result signature of every missing product
+    ///
+    /// The basis elements of a synthesized class are sorted by grade and split into groups of up to four.
+    /// Its name is made from the grades, e.g. `Grade1And3`.
+    /// Products involving the synthesized classes are not considered, so they can be missing in turn.
+    /// Returns the indices of the synthesized classes.
+    pub fn synthesize_missing_classes(&mut self) -> std::ops::Range<usize> {
+        let first_synthesized = self.classes.len();
+        let mut signatures = self.missing_products().into_iter().map(|missing| missing.signature).collect::<Vec<_>>();
+        signatures.sort_unstable();
+        signatures.dedup();
+        let algebra = self.algebra();
+        let element_lists = signatures.iter().map(|signature| algebra.sorted_elements(signature)).collect::<Vec<_>>();
+        for elements in element_lists {
+            // Groups of a single element can not be mixed with wider groups, so the sizes are balanced instead
+            let group_count = elements.len().div_ceil(4);
+            let mut grouped_basis = Vec::new();
+            let mut remaining = elements.as_slice();
+            for group_index in 0..group_count {
+                let (group, rest) = remaining.split_at(remaining.len() / (group_count - group_index));
+                grouped_basis.push(group.to_vec());
+                remaining = rest;
+            }
+            let mut grades = elements.iter().map(|element| element.grade().to_string()).collect::<Vec<_>>();
+            grades.dedup();
+            let base_name = format!("Grade{}", grades.join("And"));
+            let mut class_name = base_name.clone();
+            for suffix in 'B'..='Z' {
+                if !self.classes.iter().any(|class| class.class_name == class_name) {
+                    break;
+                }
+                class_name = format!("{}{}", base_name, suffix);
+            }
+            self.classes.push(MultiVectorClass {
+                class_name,
+                grouped_basis,
+                element_aliases: std::collections::HashMap::new(),
+            });
+        }
+        first_synthesized..self.classes.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_errors(config: &str) -> Vec<ParseError> {
+        match AlgebraDescriptor::parse_inline(config) {
+            Ok(_) => panic!("`{}` parsed without errors", config),
+            Err(errors) => errors,
+        }
+    }
+
+    #[test]
+    fn group_size() {
+        let errors = parse_errors("ppga3d:0,1,1,1;Scalar:1;V:e0,e1,e2,e3,e01");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].column, 27);
+        assert_eq!(errors[0].class_name.as_deref(), Some("V"));
+        assert_eq!(errors[0].message, "group has 5 elements, at most 4 are supported");
+        assert!(AlgebraDescriptor::parse_inline("ppga3d:0,1,1,1;Scalar:1;V:e0,e1,e2,e3|e01").is_ok());
+    }
+
+    #[test]
+    fn error_positions_and_suggestions() {
+        let errors = parse_errors("ppga3d: 0, 1, 1, 1; Scalar: 1; Point: e123, e032, e014");
+        assert_eq!(errors.len(), 1);
+        assert_eq!((errors[0].line, errors[0].column), (1, 54));
+        assert_eq!(errors[0].class_name.as_deref(), Some("Point"));
+        assert_eq!(errors[0].message, "generator 4 out of range for 4-generator algebra");
+        assert_eq!(errors[0].suggestion.as_deref(), Some("use e0 to e3"));
+        assert_eq!(
+            errors[0].to_string(),
+            "1:54: in class `Point`: generator 4 out of range for 4-generator algebra (use e0 to e3)"
+        );
+        let errors = parse_errors("ppga1d: 0, one; Scalar: 1");
+        assert_eq!(errors[0].column, 12);
+        assert_eq!(errors[0].message, "generator square `one` is not an integer");
+        assert_eq!(errors[0].suggestion.as_deref(), Some("use -1, 0 or 1"));
+        let errors = parse_errors("ppga1d: 0, 1; Scalar: 1; [Scalar]: GeometricProdcut");
+        assert_eq!(errors[0].column, 36);
+        assert_eq!(errors[0].message, "unknown trait `GeometricProdcut`");
+        assert!(errors[0].suggestion.as_ref().unwrap().contains("GeometricProduct"));
+    }
+
+    #[test]
+    fn generators_past_sixteen() {
+        let header = format!("big: {}; Scalar: 1; ", vec!["1"; 17].join(","));
+        let algebra_descriptor = AlgebraDescriptor::parse_inline(&format!("{}Bivector: e0_e1, e0_e16 | e15_e16", header)).unwrap();
+        assert_eq!(
+            algebra_descriptor.generator_names,
+            (0..17).map(|index| format!("e{}", index)).collect::<Vec<_>>()
+        );
+        let registry = algebra_descriptor.registry();
+        assert_eq!(registry.classes[1].signature(), vec![0b11, 1 << 16 | 1, 1 << 16 | 1 << 15]);
+        let errors = parse_errors(&format!("{}A: e01", header));
+        assert_eq!(errors[0].column, 55);
+        assert_eq!(errors[0].message, "`e01` is not a generator");
+        assert_eq!(
+            errors[0].suggestion.as_deref(),
+            Some("join the generators e0 to e16 with `_`, e.g. `e0_e1`")
+        );
+        let errors = parse_errors(&format!("{}A: e0_e17", header));
+        assert_eq!(errors[0].column, 58);
+        assert_eq!(errors[0].message, "`e17` is not a generator");
+    }
+
+    #[test]
+    fn reports_every_error() {
+        let errors = parse_errors("ppga1d: 0, 1; Scalar: 1; A: e2; B: x1, e01 | ; C: 1");
+        let messages = errors.iter().map(|error| (error.column, error.message.as_str())).collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                (30, "generator 2 out of range for 2-generator algebra"),
+                (36, "expected basis element, found `x1`"),
+                (45, "empty group"),
+                (51, "same basis elements as class `Scalar`"),
+            ]
+        );
+        let source = "algebra ppga1d: 0, 1\nScalar: 1\n\n# comment\nA: e02\nalgebra epga1d: 1, 1\nB e01\n";
+        let errors = match AlgebraDescriptor::parse_file(source) {
+            Ok(_) => panic!("descriptor file parsed without errors"),
+            Err(errors) => errors,
+        };
+        let positions = errors.iter().map(|error| (error.line, error.column)).collect::<Vec<_>>();
+        assert_eq!(positions, vec![(5, 6), (7, 1)]);
+        assert_eq!(errors[1].message, "expected class descriptor, found `B e01`");
+    }
+
+    #[test]
+    fn reports_every_header_error() {
+        let errors = parse_errors("3d: w=0, 1x=1, w=one, y=1, e1.e2=1; Scalar: 1");
+        let messages = errors.iter().map(|error| (error.column, error.message.as_str())).collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                (1, "`3d` is not a valid algebra name"),
+                (10, "`1x` is not a valid generator name"),
+                (16, "generator name `w` is used twice"),
+                (18, "generator square `one` is not an integer"),
+            ]
+        );
+        let errors = parse_errors("cga1d: x=1, no=0, ni=0, no.ni=-one, no.x.ni=1, x.nx=0; Scalar: 1");
+        let messages = errors.iter().map(|error| (error.column, error.message.as_str())).collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                (31, "inner product `-one` is not an integer"),
+                (37, "expected two different generators"),
+                (50, "expected basis element, found `nx`"),
+            ]
+        );
+        let source = "algebra 2d: 1, one\nScalar: 1\nalgebra epga1d: 1\nScalar: 1\n";
+        let errors = match AlgebraDescriptor::parse_file(source) {
+            Ok(_) => panic!("descriptor file parsed without errors"),
+            Err(errors) => errors,
+        };
+        let positions = errors.iter().map(|error| (error.line, error.column)).collect::<Vec<_>>();
+        assert_eq!(positions, vec![(1, 9), (1, 16)]);
+    }
+
+    fn generated_code(algebra_descriptor: &AlgebraDescriptor) -> (Vec<u8>, Vec<u8>) {
+        let mut emitter = crate::emit::Emitter {
+            rust_collector: Some(Vec::new()),
+            glsl_collector: Some(Vec::new()),
+            trait_coverage: None,
+        };
+        crate::generate(algebra_descriptor, "crate", &mut emitter).unwrap();
+        (emitter.rust_collector.unwrap(), emitter.glsl_collector.unwrap())
+    }
+
+    #[test]
+    fn inline_and_file_descriptors() {
+        let inline = AlgebraDescriptor::parse_inline(
+            "ppga2d: 0, 1, 1; Scalar: 1; MultiVector: 1, e12, e1, e2|e0, e012, e01, -e02; Rotor: 1, e12; Point: e12, e01, -e02; \
+             Plane: e0, e2, e1; Motor: 1, e12, e01, -e02; [Point]: GeometricProduct, Transformation",
+        )
+        .unwrap();
+        let source = "algebra ppga2d: 0, 1, 1 # projective\n\
+                      Scalar: 1\n\
+                      MultiVector: 1, e12, e1, e2|e0, e012, e01, -e02\n\
+                      Rotor: 1, e12\n\
+                      \n\
+                      Point: e12, e01, -e02 # points are bivectors\n\
+                      Plane: e0, e2, e1\n\
+                      Motor: 1, e12, e01, -e02\n\
+                      [Point]: GeometricProduct, Transformation\n";
+        let file = AlgebraDescriptor::parse_file(source).unwrap();
+        assert_eq!(file.len(), 1);
+        assert_eq!(file[0].registry().classes, inline.registry().classes);
+        assert_eq!(file[0].trait_selections, inline.trait_selections);
+        let (rust_code, glsl_code) = generated_code(&inline);
+        assert!(!rust_code.is_empty() && !glsl_code.is_empty());
+        assert!(generated_code(&file[0]) == (rust_code, glsl_code));
+    }
+
+    #[test]
+    fn sixty_four_generators() {
+        let pseudoscalar = (0..64).map(|index| format!("e{}", index)).collect::<Vec<_>>().join("_");
+        let algebra_descriptor = AlgebraDescriptor::parse_inline(&format!(
+            "big: {}; Scalar: 1; Vector: e0, e1 | e62, e63; Pseudoscalar: {}",
+            vec!["1"; 64].join(","),
+            pseudoscalar
+        ))
+        .unwrap();
+        let (rust_code, _glsl_code) = generated_code(&algebra_descriptor);
+        let rust_code = String::from_utf8(rust_code).unwrap();
+        assert!(rust_code.contains("fn grade_select(self, grade_mask: u128) -> Pseudoscalar"));
+        assert!(rust_code.contains(&format!("if grade_mask & {} == 0 {{", 1u128 << 64)));
+    }
+
+    #[test]
+    fn optional_traits() {
+        let descriptor = AlgebraDescriptor::parse_inline("ppga1d: 0, 1; Scalar: 1; A: 1, e01; [A]: CommutatorProduct").unwrap();
+        assert!(descriptor.is_trait_selected("CommutatorProduct", "A", Some("A")));
+        assert!(!descriptor.is_trait_selected("CommutatorProduct", "Scalar", Some("A")));
+        assert!(descriptor.is_trait_selected("GeometricProduct", "Scalar", Some("A")));
+        let descriptor = AlgebraDescriptor::parse_inline("ppga1d: 0, 1; Scalar: 1; A: 1, e01; enable CommutatorProduct").unwrap();
+        assert!(descriptor.is_trait_selected("CommutatorProduct", "Scalar", Some("A")));
+        assert!(!descriptor.is_trait_selected("AnticommutatorProduct", "Scalar", Some("A")));
+        let errors = parse_errors("ppga1d: 0, 1; Scalar: 1; enable CommutatorProduct, GeometricProduct");
+        assert_eq!(
+            (errors[0].column, errors[0].message.as_str()),
+            (52, "`GeometricProduct` can not be enabled")
+        );
+    }
+}
@@ -1,17 +1,18 @@
-use crate::{algebra::BasisElement, ast::AstNode, glsl, rust};
+use crate::{algebra::BasisElement, ast::AstNode, coverage::TraitCoverage, glsl, rust};
 
+/// Words start with an upper case letter or a number, e.g. `GeometricProduct` becomes `geometric_product` and `Grade2` becomes `grade_2`
 pub fn camel_to_snake_case<W: std::io::Write>(collector: &mut W, name: &str) -> std::io::Result<()> {
-    let mut underscores = name.chars().enumerate().filter(|(_i, c)| c.is_uppercase()).map(|(i, _c)| i).peekable();
-    for (i, c) in name.to_lowercase().bytes().enumerate() {
-        if let Some(next_underscores) = underscores.peek() {
-            if i == *next_underscores {
-                if i > 0 {
-                    collector.write_all(b"_")?;
-                }
-                underscores.next();
+    let mut previous: Option<char> = None;
+    for c in name.chars() {
+        if let Some(previous) = previous {
+            if c.is_uppercase() || (c.is_ascii_digit() && !previous.is_ascii_digit()) {
+                collector.write_all(b"_")?;
             }
         }
-        collector.write_all(&[c])?;
+        for lower_case in c.to_lowercase() {
+            collector.write_fmt(format_args!("{}", lower_case))?;
+        }
+        previous = Some(c);
     }
     Ok(())
 }
@@ -23,40 +24,72 @@ pub fn emit_indentation<W: std::io::Write>(collector: &mut W, indentation: usize
     Ok(())
 }
 
-pub fn emit_element_name<W: std::io::Write>(collector: &mut W, element: &BasisElement) -> std::io::Result<()> {
+pub fn emit_element_name<W: std::io::Write>(collector: &mut W, element: &BasisElement, generator_names: &[String]) -> std::io::Result<()> {
     debug_assert_ne!(element.scalar, 0);
     if element.index == 0 {
         collector.write_all(b"scalar")
     } else {
-        collector.write_all(if element.scalar < 0 { b"_e" } else { b"e" })?;
-        collector.write_all(
-            element
-                .component_bits()
-                .map(|index| format!("{:X}", index))
-                .collect::<String>()
-                .as_bytes(),
-        )
+        if element.scalar < 0 {
+            collector.write_all(b"_")?;
+        }
+        collector.write_all(element.name(generator_names).as_bytes())
+    }
+}
+
+/// Name of the getter and setter of an element, which ignores its sign, e.g. `e02` for `-e02`
+pub fn element_accessor_name(element: &BasisElement, generator_names: &[String]) -> String {
+    if element.index == 0 {
+        "scalar".to_string()
+    } else {
+        element.name(generator_names)
     }
 }
 
+/// Signed name as used in comments and debug output, e.g. `-e02`
+pub fn element_label(element: &BasisElement, generator_names: &[String]) -> String {
+    format!("{}{}", if element.scalar < 0 { "-" } else { "" }, element.name(generator_names))
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Backend {
+    Rust,
+    Glsl,
+}
+
 pub struct Emitter<W: std::io::Write> {
-    pub rust_collector: W,
-    pub glsl_collector: W,
+    pub rust_collector: Option<W>,
+    pub glsl_collector: Option<W>,
+    pub trait_coverage: Option<TraitCoverage>,
 }
 
 impl Emitter<std::fs::File> {
-    pub fn new(path: &std::path::Path) -> Self {
-        Self {
-            rust_collector: std::fs::File::create(path.with_extension("rs")).unwrap(),
-            glsl_collector: std::fs::File::create(path.with_extension("glsl")).unwrap(),
-        }
+    pub fn new(path: &std::path::Path, backends: &[Backend]) -> std::io::Result<Self> {
+        let create = |backend: Backend, extension: &str| -> std::io::Result<Option<std::fs::File>> {
+            if backends.contains(&backend) {
+                std::fs::File::create(path.with_extension(extension)).map(Some)
+            } else {
+                Ok(None)
+            }
+        };
+        Ok(Self {
+            rust_collector: create(Backend::Rust, "rs")?,
+            glsl_collector: create(Backend::Glsl, "glsl")?,
+            trait_coverage: None,
+        })
     }
 }
 
 impl<W: std::io::Write> Emitter<W> {
     pub fn emit(&mut self, ast_node: &AstNode) -> std::io::Result<()> {
-        rust::emit_code(&mut self.rust_collector, ast_node, 0)?;
-        glsl::emit_code(&mut self.glsl_collector, ast_node, 0)?;
+        if let Some(collector) = &mut self.rust_collector {
+            rust::emit_code(collector, ast_node, 0)?;
+        }
+        if let Some(collector) = &mut self.glsl_collector {
+            glsl::emit_code(collector, ast_node, 0)?;
+        }
+        if let Some(trait_coverage) = &mut self.trait_coverage {
+            trait_coverage.record(ast_node);
+        }
         Ok(())
     }
 }
@@ -1,13 +1,13 @@
 use crate::{
-    ast::{AstNode, DataType, Expression, ExpressionContent},
-    emit::{camel_to_snake_case, emit_indentation},
+    ast::{grade_mask, AstNode, DataType, Expression, ExpressionContent, ScalarFunction},
+    emit::{camel_to_snake_case, element_label, emit_indentation},
 };
 
 const COMPONENT: &[&str] = &["x", "y", "z", "w"];
 
 fn emit_data_type<W: std::io::Write>(collector: &mut W, data_type: &DataType) -> std::io::Result<()> {
     match data_type {
-        DataType::Integer => collector.write_all(b"int"),
+        DataType::Integer | DataType::GradeMask => collector.write_all(b"int"),
         DataType::SimdVector(size) if *size == 1 => collector.write_all(b"float"),
         DataType::SimdVector(size) => collector.write_fmt(format_args!("vec{}", *size)),
         DataType::MultiVector(class) if class.is_scalar() => collector.write_all(b"float"),
@@ -15,6 +15,17 @@ fn emit_data_type<W: std::io::Write>(collector: &mut W, data_type: &DataType) ->
     }
 }
 
+/// Emits an operand of `operator`, parenthesized if it binds weaker than `operator`
+fn emit_operand<W: std::io::Write>(collector: &mut W, operator: &ExpressionContent, operand: &Expression, is_rhs: bool) -> std::io::Result<()> {
+    if operand.needs_parentheses(operator, is_rhs) {
+        collector.write_all(b"(")?;
+        emit_expression(collector, operand)?;
+        collector.write_all(b")")
+    } else {
+        emit_expression(collector, operand)
+    }
+}
+
 fn emit_expression<W: std::io::Write>(collector: &mut W, expression: &Expression) -> std::io::Result<()> {
     match &expression.content {
         ExpressionContent::None => unreachable!(),
@@ -120,6 +131,7 @@ fn emit_expression<W: std::io::Write>(collector: &mut W, expression: &Expression
         }
         ExpressionContent::Constant(data_type, values) => match data_type {
             DataType::Integer => collector.write_fmt(format_args!("{}", values[0] as f32))?,
+            DataType::GradeMask => collector.write_fmt(format_args!("{}", grade_mask(values)))?,
             DataType::SimdVector(_size) => {
                 if expression.size == 1 {
                     collector.write_fmt(format_args!("{:.1}", values[0] as f32))?
@@ -138,6 +150,26 @@ fn emit_expression<W: std::io::Write>(collector: &mut W, expression: &Expression
             emit_expression(collector, inner_expression)?;
             collector.write_all(b")")?;
         }
+        ExpressionContent::Function(function, inner_expression) => {
+            collector.write_all(match function {
+                ScalarFunction::Cosine => b"cos(",
+                ScalarFunction::Sine => b"sin(",
+                ScalarFunction::HyperbolicCosine => b"cosh(",
+                ScalarFunction::HyperbolicSine => b"sinh(",
+                ScalarFunction::HyperbolicArcTangent => b"atanh(",
+                ScalarFunction::Exponential => b"exp(",
+                ScalarFunction::Logarithm => b"log(",
+            })?;
+            emit_expression(collector, inner_expression)?;
+            collector.write_all(b")")?;
+        }
+        ExpressionContent::ArcTangent2(lhs, rhs) => {
+            collector.write_all(b"atan(")?;
+            emit_expression(collector, lhs)?;
+            collector.write_all(b", ")?;
+            emit_expression(collector, rhs)?;
+            collector.write_all(b")")?;
+        }
         ExpressionContent::Add(lhs, rhs)
         | ExpressionContent::Subtract(lhs, rhs)
         | ExpressionContent::Multiply(lhs, rhs)
@@ -149,7 +181,7 @@ fn emit_expression<W: std::io::Write>(collector: &mut W, expression: &Expression
             if let ExpressionContent::LogicAnd(_, _) = expression.content {
                 collector.write_all(b"(")?;
             }
-            emit_expression(collector, lhs)?;
+            emit_operand(collector, &expression.content, lhs, false)?;
             collector.write_all(match expression.content {
                 ExpressionContent::Add(_, _) => b" + ",
                 ExpressionContent::Subtract(_, _) => b" - ",
@@ -161,7 +193,7 @@ fn emit_expression<W: std::io::Write>(collector: &mut W, expression: &Expression
                 ExpressionContent::BitShiftRight(_, _) => b" >> ",
                 _ => unreachable!(),
             })?;
-            emit_expression(collector, rhs)?;
+            emit_operand(collector, &expression.content, rhs, true)?;
             if let ExpressionContent::LogicAnd(_, _) = expression.content {
                 collector.write_all(b")")?;
             }
@@ -173,8 +205,8 @@ fn emit_expression<W: std::io::Write>(collector: &mut W, expression: &Expression
 pub fn emit_code<W: std::io::Write>(collector: &mut W, ast_node: &AstNode, indentation: usize) -> std::io::Result<()> {
     match ast_node {
         AstNode::None => {}
-        AstNode::Preamble => {}
-        AstNode::ClassDefinition { class } => {
+        AstNode::Preamble { .. } | AstNode::TraitDefinition { .. } => {}
+        AstNode::ClassDefinition { class, generator_names } => {
             if class.is_scalar() {
                 return Ok(());
             }
@@ -186,7 +218,7 @@ pub fn emit_code<W: std::io::Write>(collector: &mut W, ast_node: &AstNode, inden
                     if i > 0 {
                         collector.write_all(b", ")?;
                     }
-                    collector.write_fmt(format_args!("{}", element))?;
+                    collector.write_all(element_label(element, generator_names).as_bytes())?;
                 }
                 collector.write_all(b"\n")?;
                 emit_indentation(collector, indentation + 1)?;
@@ -0,0 +1,258 @@
+//! Grade filters of user-defined products, e.g. `t == |r-s|`
+//!
+//! `r` and `s` are the grades of the factors, `t` the grade of the product and `n` the dimension of the algebra.
+//! Comparisons and logic evaluate to 0 or 1, and any value other than 0 keeps a term of the geometric product.
+
+use crate::dsl::ParseError;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Operator {
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    LessThan,
+    LessOrEqual,
+    GreaterThan,
+    GreaterOrEqual,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Term {
+    Integer(isize),
+    Variable(char),
+    Negate(Box<Term>),
+    Not(Box<Term>),
+    Absolute(Box<Term>),
+    Binary(Operator, Box<Term>, Box<Term>),
+}
+
+/// Operators by ascending precedence, the longer spelling first where one is a prefix of another
+const BINARY_OPERATORS: &[&[(&str, Operator)]] = &[
+    &[("||", Operator::Or)],
+    &[("&&", Operator::And)],
+    &[
+        ("==", Operator::Equal),
+        ("!=", Operator::NotEqual),
+        ("<=", Operator::LessOrEqual),
+        (">=", Operator::GreaterOrEqual),
+        ("<", Operator::LessThan),
+        (">", Operator::GreaterThan),
+    ],
+    &[("+", Operator::Add), ("-", Operator::Subtract)],
+    &[("*", Operator::Multiply), ("/", Operator::Divide), ("%", Operator::Remainder)],
+];
+
+struct Parser<'a> {
+    source: &'a str,
+    position: usize,
+    column: usize,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        self.position = self.source.len() - self.source[self.position..].trim_start().len();
+    }
+
+    fn error(&self, message: String) -> ParseError {
+        ParseError::new(self.column + self.position, message)
+    }
+
+    fn accept(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        if self.source[self.position..].starts_with(token) {
+            self.position += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_binary(&mut self, precedence: usize) -> Result<Term, ParseError> {
+        if precedence == BINARY_OPERATORS.len() {
+            return self.parse_unary();
+        }
+        let mut lhs = self.parse_binary(precedence + 1)?;
+        'operators: loop {
+            for (token, operator) in BINARY_OPERATORS[precedence].iter() {
+                if self.accept(token) {
+                    let rhs = self.parse_binary(precedence + 1)?;
+                    lhs = Term::Binary(*operator, Box::new(lhs), Box::new(rhs));
+                    continue 'operators;
+                }
+            }
+            return Ok(lhs);
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Term, ParseError> {
+        if self.accept("-") {
+            Ok(Term::Negate(Box::new(self.parse_unary()?)))
+        } else if self.accept("!") {
+            Ok(Term::Not(Box::new(self.parse_unary()?)))
+        } else {
+            self.parse_primary()
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Term, ParseError> {
+        if self.accept("(") {
+            let term = self.parse_binary(0)?;
+            if !self.accept(")") {
+                return Err(self.error("expected `)`".to_string()));
+            }
+            return Ok(term);
+        }
+        // `||` is the logical or, while `|` alone encloses an absolute value
+        if !self.source[self.position..].trim_start().starts_with("||") && self.accept("|") {
+            let term = self.parse_binary(0)?;
+            if !self.accept("|") {
+                return Err(self.error("expected `|`".to_string()));
+            }
+            return Ok(Term::Absolute(Box::new(term)));
+        }
+        let rest = &self.source[self.position..];
+        let length = rest.find(|c: char| !c.is_ascii_alphanumeric() && c != '_').unwrap_or(rest.len());
+        let token = &rest[..length];
+        let term = match token {
+            "r" | "s" | "t" | "n" => Term::Variable(token.chars().next().unwrap()),
+            _ if !token.is_empty() && token.chars().all(|c| c.is_ascii_digit()) => {
+                Term::Integer(token.parse().map_err(|_| self.error(format!("`{}` is too large", token)))?)
+            }
+            "" => return Err(self.error("expected a grade, a number or `(`".to_string())),
+            _ => {
+                return Err(self.error(format!("unknown variable `{}`", token)).with_suggestion(
+                    "use `r` and `s` for the grades of the factors, `t` for the grade of the product and `n` for the dimension".to_string(),
+                ))
+            }
+        };
+        self.position += length;
+        Ok(term)
+    }
+}
+
+/// Condition on the grades `r`, `s` and `t` of the terms `a * b -> c` of the geometric product
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct GradeFilter {
+    source: String,
+    term: Term,
+}
+
+impl GradeFilter {
+    pub fn parse(source: &str, column: usize) -> Result<Self, ParseError> {
+        let mut parser = Parser { source, position: 0, column };
+        let term = parser.parse_binary(0)?;
+        parser.skip_whitespace();
+        if parser.position < source.len() {
+            return Err(parser.error(format!("unexpected `{}`", &source[parser.position..])));
+        }
+        Ok(Self {
+            source: source.to_string(),
+            term,
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn evaluate(&self, r: usize, s: usize, t: usize, dimension: usize) -> bool {
+        Self::evaluate_term(&self.term, [r as isize, s as isize, t as isize, dimension as isize]) != 0
+    }
+
+    /// Division by zero evaluates to 0, so that a filter never panics
+    fn evaluate_term(term: &Term, grades: [isize; 4]) -> isize {
+        let evaluate = |term: &Term| Self::evaluate_term(term, grades);
+        match term {
+            Term::Integer(value) => *value,
+            Term::Variable(name) => grades["rstn".find(*name).unwrap()],
+            Term::Negate(term) => -evaluate(term),
+            Term::Not(term) => (evaluate(term) == 0) as isize,
+            Term::Absolute(term) => evaluate(term).abs(),
+            Term::Binary(operator, lhs, rhs) => {
+                let (lhs, rhs) = (evaluate(lhs), evaluate(rhs));
+                match operator {
+                    Operator::Or => (lhs != 0 || rhs != 0) as isize,
+                    Operator::And => (lhs != 0 && rhs != 0) as isize,
+                    Operator::Equal => (lhs == rhs) as isize,
+                    Operator::NotEqual => (lhs != rhs) as isize,
+                    Operator::LessThan => (lhs < rhs) as isize,
+                    Operator::LessOrEqual => (lhs <= rhs) as isize,
+                    Operator::GreaterThan => (lhs > rhs) as isize,
+                    Operator::GreaterOrEqual => (lhs >= rhs) as isize,
+                    Operator::Add => lhs + rhs,
+                    Operator::Subtract => lhs - rhs,
+                    Operator::Multiply => lhs * rhs,
+                    Operator::Divide => lhs.checked_div(rhs).unwrap_or(0),
+                    Operator::Remainder => lhs.checked_rem(rhs).unwrap_or(0),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Grades `(r, s, t)` of the terms of the geometric product in four dimensions which `source` keeps
+    fn kept_grades(source: &str) -> Vec<(usize, usize, usize)> {
+        let grade_filter = GradeFilter::parse(source, 0).unwrap();
+        let mut kept_grades = Vec::new();
+        for r in 0..=4 {
+            for s in 0..=4 {
+                for t in (r.max(s) - r.min(s)..=(r + s).min(8 - r - s)).step_by(2) {
+                    if grade_filter.evaluate(r, s, t, 4) {
+                        kept_grades.push((r, s, t));
+                    }
+                }
+            }
+        }
+        kept_grades
+    }
+
+    fn all_grades(condition: impl Fn(usize, usize, usize) -> bool) -> Vec<(usize, usize, usize)> {
+        kept_grades("1").into_iter().filter(|(r, s, t)| condition(*r, *s, *t)).collect()
+    }
+
+    #[test]
+    fn products() {
+        assert_eq!(kept_grades("t == r + s"), all_grades(|r, s, t| t == r + s));
+        assert_eq!(kept_grades("t == s - r"), all_grades(|r, s, t| r <= s && t == s - r));
+        assert_eq!(kept_grades("t == |r-s|"), all_grades(|r, s, t| t == r.max(s) - r.min(s)));
+        assert_eq!(kept_grades("t == 0"), all_grades(|_r, _s, t| t == 0));
+        assert_eq!(kept_grades("r + s - t == n"), all_grades(|r, s, t| r + s == t + 4));
+        assert_eq!(kept_grades("0"), vec![]);
+    }
+
+    #[test]
+    fn precedence_and_absolute_value() {
+        let evaluate = |source: &str| GradeFilter::parse(source, 0).unwrap().evaluate(1, 3, 2, 4);
+        assert!(evaluate("r + s * 2 == 7"));
+        assert!(evaluate("(r + s) * 2 == 8"));
+        assert!(evaluate("|r - s| == t && t < n"));
+        assert!(evaluate("r == 0 || |r - s| == t"));
+        assert!(evaluate("-r + s == t"));
+        assert!(evaluate("!(r == s)"));
+        assert!(evaluate("n % 3 == r"));
+        assert!(!evaluate("r / 0"));
+    }
+
+    #[test]
+    fn errors() {
+        let error = GradeFilter::parse("t == |r-x|", 10).unwrap_err();
+        assert_eq!((error.column, error.message.as_str()), (18, "unknown variable `x`"));
+        assert!(error.suggestion.is_some());
+        let error = GradeFilter::parse("(t == r", 10).unwrap_err();
+        assert_eq!((error.column, error.message.as_str()), (17, "expected `)`"));
+        let error = GradeFilter::parse("t == r s", 0).unwrap_err();
+        assert_eq!((error.column, error.message.as_str()), (7, "unexpected `s`"));
+        let error = GradeFilter::parse("t ==", 0).unwrap_err();
+        assert_eq!(error.message, "expected a grade, a number or `(`");
+    }
+}
@@ -0,0 +1,1009 @@
+//! Generates the multiplication tables of geometric algebras and emits them as source code
+//!
+//! Besides the `codegen` binary, this library can be used from a `build.rs` script
+//! to generate custom algebras into `OUT_DIR`:
+//!
+//! ```no_run
+//! let out_dir = std::path::PathBuf::from(std::env::var("OUT_DIR").unwrap());
+//! let algebra_descriptor = codegen::dsl::AlgebraDescriptor::parse_inline("ppga1d:0,1;Scalar:1;DualNumber:1,e01").unwrap();
+//! codegen::generate_rust_module(&algebra_descriptor, &out_dir).unwrap();
+//! ```
+//!
+//! The resulting file can then be included into a module of the downstream crate,
+//! which needs to depend on `geometric_algebra` for the SIMD types and traits:
+//!
+//! ```ignore
+//! pub mod ppga1d {
+//!     include!(concat!(env!("OUT_DIR"), "/ppga1d.rs"));
+//! }
+//! ```
+
+pub mod algebra;
+pub mod ast;
+mod compile;
+pub mod coverage;
+pub mod dsl;
+pub mod emit;
+mod glsl;
+pub mod grade_filter;
+mod metric;
+mod rust;
+pub mod tables;
+
+use crate::{
+    algebra::{Involution, MultiVectorClass, MultiVectorClassRegistry},
+    ast::{AstNode, DataType, Parameter},
+    compile::GradeNegation,
+    coverage::TraitCoverage,
+    dsl::AlgebraDescriptor,
+    emit::{Backend, Emitter},
+};
+use std::collections::BTreeMap;
+
+/// Traits emitted by `generate` besides the involutions and products of the algebra
+pub const GENERATED_TRAITS: &[&str] = &[
+    "Zero",
+    "One",
+    "Into",
+    "Add",
+    "Sub",
+    "Mul",
+    "Div",
+    "SquaredMagnitude",
+    "Magnitude",
+    "Signum",
+    "Inverse",
+    "BulkNorm",
+    "WeightNorm",
+    "GeometricNorm",
+    "Unitize",
+    "Powi",
+    "GradeSelect",
+    "GeometricQuotient",
+    "Transformation",
+    "AntiTransformation",
+    "ProjectOnto",
+    "RejectFrom",
+    "ReflectIn",
+    "Exp",
+    "Ln",
+    "Powf",
+    "Sqrt",
+];
+
+/// Traits only emitted where the descriptor enables them with `enable` or selects them for a class,
+/// as they would about double the size of the generated code
+pub const OPTIONAL_TRAITS: &[&str] = &[
+    "CommutatorProduct",
+    "AnticommutatorProduct",
+    "GeometricAntiProduct",
+    "AntiDotProduct",
+    "AntiTransformation",
+];
+
+/// Sandwich products as trait name, product and the involution applied to the versor on the right
+const SANDWICH_PRODUCTS: &[(&str, &str, &str)] = &[
+    ("Transformation", "GeometricProduct", "Reversal"),
+    ("AntiTransformation", "GeometricAntiProduct", "AntiReversal"),
+];
+
+/// Projections as trait name and the product with the target, which is then multiplied by the inverse of the target
+const PROJECTIONS: &[(&str, &str)] = &[("ProjectOnto", "InnerProduct"), ("RejectFrom", "OuterProduct")];
+
+/// Classes of larger algebras only get an inverse if their elements are versors, see `AlgebraDescriptor::classes_without_inverse`
+pub const MAX_GENERAL_INVERSE_GENERATORS: usize = 5;
+
+/// Writes `<name>.rs` into `output_directory`, referring to the `geometric_algebra` crate for the SIMD types and traits
+pub fn generate_rust_module(algebra_descriptor: &AlgebraDescriptor, output_directory: &std::path::Path) -> std::io::Result<std::path::PathBuf> {
+    let path = output_directory.join(&algebra_descriptor.name);
+    let mut emitter = Emitter::new(&path, &[Backend::Rust])?;
+    generate(algebra_descriptor, "geometric_algebra", &mut emitter)?;
+    Ok(path.with_extension("rs"))
+}
+
+/// The pairs of classes which have only one of the projection and the rejection, as the missing trait and the class names of both parameters
+///
+/// These are left out when an intermediate product has no class and the rejection can not be derived from the projection.
+pub fn missing_projections(algebra_descriptor: &AlgebraDescriptor) -> std::io::Result<Vec<(&'static str, String, String)>> {
+    let mut emitter = Emitter::<std::io::Sink> {
+        rust_collector: None,
+        glsl_collector: None,
+        trait_coverage: Some(TraitCoverage::new(algebra_descriptor)),
+    };
+    generate(algebra_descriptor, "geometric_algebra", &mut emitter)?;
+    let implementations = emitter
+        .trait_coverage
+        .map(|trait_coverage| trait_coverage.implementations)
+        .unwrap_or_default();
+    let has_implementation = |trait_name: &str, parameters: &[String]| {
+        implementations
+            .iter()
+            .any(|implementation| implementation.trait_name == trait_name && implementation.parameters == parameters)
+    };
+    // The projection or the rejection is zero if its product of the classes is, e.g. the rejection of a plane from itself
+    let registry = algebra_descriptor.registry();
+    let is_zero = |product_name: &str, parameters: &[String]| {
+        let class = |class_name: &String| registry.classes.iter().find(|class| class.class_name == *class_name);
+        match (class(&parameters[0]), class(&parameters[1])) {
+            (Some(class_a), Some(class_b)) => algebra_descriptor
+                .products(&class_a.flat_basis(), &class_b.flat_basis())
+                .iter()
+                .any(|(name, product)| *name == product_name && MultiVectorClass::product_signature(product, class_a, class_b).is_empty()),
+            _ => false,
+        }
+    };
+    let mut missing = Vec::new();
+    for implementation in implementations.iter() {
+        for ((name, _), (other_name, other_product_name)) in PROJECTIONS.iter().zip(PROJECTIONS.iter().rev()) {
+            if implementation.trait_name == *name
+                && !has_implementation(other_name, &implementation.parameters)
+                && !is_zero(other_product_name, &implementation.parameters)
+            {
+                missing.push((*other_name, implementation.parameters[0].clone(), implementation.parameters[1].clone()));
+            }
+        }
+    }
+    Ok(missing)
+}
+
+/// Emits all classes and trait implementations of an algebra
+///
+/// `runtime_crate` is the path the generated Rust code imports the SIMD types and traits from.
+pub fn generate<W: std::io::Write>(algebra_descriptor: &AlgebraDescriptor, runtime_crate: &str, emitter: &mut Emitter<W>) -> std::io::Result<()> {
+    let algebra = algebra_descriptor.algebra();
+    let registry = algebra_descriptor.registry();
+    emitter.emit(&AstNode::Preamble { runtime_crate })?;
+    for trait_definition in algebra_descriptor.trait_definitions().iter() {
+        emitter.emit(trait_definition)?;
+    }
+    for class in registry.classes.iter() {
+        emitter.emit(&AstNode::ClassDefinition {
+            class,
+            generator_names: algebra.generator_names,
+        })?;
+    }
+    let mut ast_nodes = Vec::new();
+    let mut trait_implementations = TraitImplementations::default();
+    for class_a in registry.classes.iter() {
+        let mut class_implementations = derive_class_implementations(algebra_descriptor, &registry, class_a, &mut ast_nodes);
+        derive_norms(algebra_descriptor, &mut class_implementations, &mut ast_nodes);
+        trait_implementations.0.insert(class_a.class_name.clone(), class_implementations);
+    }
+    let scalar_class = registry.classes.iter().find(|class| class.is_scalar());
+    let inverses = derive_general_inverses(algebra_descriptor, &registry, scalar_class, &trait_implementations, &mut ast_nodes);
+    for class_implementations in trait_implementations.0.values() {
+        for (parameter_b, _) in class_implementations.pairs.values() {
+            derive_powers_and_quotients(
+                &trait_implementations,
+                &inverses,
+                &class_implementations.parameter,
+                parameter_b,
+                &mut ast_nodes,
+            );
+            derive_sandwich_products(
+                algebra_descriptor,
+                &registry,
+                &trait_implementations,
+                &class_implementations.parameter,
+                parameter_b,
+                &mut ast_nodes,
+            );
+            derive_projections(
+                &registry,
+                &trait_implementations,
+                &inverses,
+                &class_implementations.parameter,
+                parameter_b,
+                &mut ast_nodes,
+            );
+        }
+    }
+    if let Some(scalar_class) = scalar_class {
+        let exponentials = derive_exponentials(&registry, scalar_class, &trait_implementations, &mut ast_nodes);
+        derive_logarithms(&registry, scalar_class, &trait_implementations, &exponentials, &mut ast_nodes);
+        derive_complex_functions(&registry, scalar_class, &trait_implementations, &mut ast_nodes);
+        derive_square_roots(algebra_descriptor, scalar_class, &trait_implementations, &mut ast_nodes);
+    }
+    let selected = select_trait_implementations(algebra_descriptor, &ast_nodes);
+    for (ast_node, selected) in ast_nodes.iter().zip(selected) {
+        if selected {
+            emitter.emit(ast_node)?;
+        }
+    }
+    Ok(())
+}
+
+/// The trait implementations of a class, which the derived trait implementations are composed of
+struct ClassImplementations<'a> {
+    parameter: Parameter<'a>,
+    /// Trait implementations of `parameter` alone by trait name
+    single: BTreeMap<String, AstNode<'a>>,
+    /// Trait implementations of `parameter` and another parameter by class name of the other parameter and trait name
+    pairs: BTreeMap<String, (Parameter<'a>, BTreeMap<String, AstNode<'a>>)>,
+}
+
+/// The trait implementations of all classes by class name
+#[derive(Default)]
+struct TraitImplementations<'a>(BTreeMap<String, ClassImplementations<'a>>);
+
+impl<'a> TraitImplementations<'a> {
+    fn single(&self, class: &MultiVectorClass, name: &str) -> Option<&AstNode<'a>> {
+        self.0.get(&class.class_name)?.single.get(name)
+    }
+
+    fn pair(&self, class_a: &MultiVectorClass, class_b: &MultiVectorClass, name: &str) -> Option<&AstNode<'a>> {
+        self.0.get(&class_a.class_name)?.pairs.get(&class_b.class_name)?.1.get(name)
+    }
+}
+
+/// Constants, involutions, grade selection, conversions, element wise operations and products of a class
+fn derive_class_implementations<'a>(
+    algebra_descriptor: &AlgebraDescriptor,
+    registry: &'a MultiVectorClassRegistry,
+    class_a: &'a MultiVectorClass,
+    ast_nodes: &mut Vec<AstNode<'a>>,
+) -> ClassImplementations<'a> {
+    let parameter_a = Parameter {
+        name: "self",
+        data_type: DataType::MultiVector(class_a),
+    };
+    let mut single_trait_implementations = BTreeMap::new();
+    for name in &["Zero", "One"] {
+        let ast_node = class_a.constant(name);
+        ast_nodes.push(ast_node.clone());
+        if ast_node != AstNode::None {
+            single_trait_implementations.insert(name.to_string(), ast_node);
+        }
+    }
+    for (name, involution) in algebra_descriptor.involutions(&class_a.flat_basis()).iter() {
+        let ast_node = MultiVectorClass::involution(name, involution, &parameter_a, registry, false);
+        ast_nodes.push(ast_node.clone());
+        if ast_node != AstNode::None {
+            single_trait_implementations.insert(name.to_string(), ast_node);
+        }
+    }
+    let grade_select = MultiVectorClass::derive_grade_select(
+        "GradeSelect",
+        &parameter_a,
+        &Parameter {
+            name: "grade_mask",
+            data_type: DataType::GradeMask,
+        },
+    );
+    ast_nodes.push(grade_select.clone());
+    single_trait_implementations.insert(result_of_trait!(grade_select).name.to_string(), grade_select);
+    let mut pair_trait_implementations = BTreeMap::new();
+    for class_b in registry.classes.iter() {
+        let mut trait_implementations = BTreeMap::new();
+        let parameter_b = Parameter {
+            name: "other",
+            data_type: DataType::MultiVector(class_b),
+        };
+        if class_a != class_b {
+            let name = "Into";
+            let mut ast_node = MultiVectorClass::involution(name, &Involution::projection(class_b), &parameter_a, registry, true);
+            // Classes containing all elements of `class_a` are filled up with zeros,
+            // except for scalars, as f32 can not implement `Into` for the classes of all algebras
+            let signature_b = class_b.signature();
+            if ast_node == AstNode::None && !class_a.is_scalar() && class_a.signature().iter().all(|index| signature_b.contains(index)) {
+                ast_node = MultiVectorClass::widening(name, &parameter_a, class_b);
+            }
+            ast_nodes.push(ast_node.clone());
+            if ast_node != AstNode::None {
+                trait_implementations.insert(name.to_string(), ast_node);
+            }
+        }
+        for name in &["Add", "Sub"] {
+            let ast_node = MultiVectorClass::element_wise(name, &parameter_a, &parameter_b, registry);
+            ast_nodes.push(ast_node.clone());
+            if ast_node != AstNode::None {
+                trait_implementations.insert(name.to_string(), ast_node);
+            }
+        }
+        if class_a == class_b {
+            for name in &["Mul", "Div"] {
+                let ast_node = MultiVectorClass::element_wise(name, &parameter_a, &parameter_b, registry);
+                ast_nodes.push(ast_node.clone());
+                if ast_node != AstNode::None {
+                    trait_implementations.insert(name.to_string(), ast_node);
+                }
+            }
+        }
+        for (name, product) in algebra_descriptor.products(&class_a.flat_basis(), &class_b.flat_basis()).iter() {
+            let ast_node = MultiVectorClass::product(name, product, &parameter_a, &parameter_b, registry);
+            ast_nodes.push(ast_node.clone());
+            if ast_node != AstNode::None {
+                trait_implementations.insert(name.to_string(), ast_node);
+            }
+        }
+        pair_trait_implementations.insert(class_b.class_name.clone(), (parameter_b, trait_implementations));
+    }
+    ClassImplementations {
+        parameter: parameter_a,
+        single: single_trait_implementations,
+        pairs: pair_trait_implementations,
+    }
+}
+
+/// Magnitudes, norms, scaling, signum, unitization and the inverse of versors, which are composed of the products of a class
+fn derive_norms<'a>(algebra_descriptor: &AlgebraDescriptor, class_implementations: &mut ClassImplementations<'a>, ast_nodes: &mut Vec<AstNode<'a>>) {
+    let ClassImplementations {
+        parameter: parameter_a,
+        single: single_trait_implementations,
+        pairs: pair_trait_implementations,
+    } = class_implementations;
+    let class_a = parameter_a.multi_vector_class();
+    if let Some((_, pair_trait_implementations)) = pair_trait_implementations.get(&class_a.class_name) {
+        if let (Some(scalar_product), Some(reversal)) = (
+            pair_trait_implementations.get("ScalarProduct"),
+            single_trait_implementations.get("Reversal"),
+        ) {
+            let squared_magnitude = MultiVectorClass::derive_squared_magnitude("SquaredMagnitude", scalar_product, reversal, parameter_a);
+            ast_nodes.push(squared_magnitude.clone());
+            let magnitude = MultiVectorClass::derive_magnitude("Magnitude", &squared_magnitude, parameter_a);
+            ast_nodes.push(magnitude.clone());
+            single_trait_implementations.insert(result_of_trait!(squared_magnitude).name.to_string(), squared_magnitude);
+            single_trait_implementations.insert(result_of_trait!(magnitude).name.to_string(), magnitude);
+        }
+    }
+    // The magnitude of a projective geometric algebra only measures the weight, as the bulk contains the degenerate generator
+    if algebra_descriptor.algebra().is_degenerate() {
+        if let Some((_, pair_trait_implementations)) = pair_trait_implementations.get(&class_a.class_name) {
+            if let Some(anti_dot_product) = pair_trait_implementations.get("AntiDotProduct") {
+                let bulk_norm = MultiVectorClass::derive_bulk_norm("BulkNorm", anti_dot_product, parameter_a);
+                ast_nodes.push(bulk_norm.clone());
+                single_trait_implementations.insert(result_of_trait!(bulk_norm).name.to_string(), bulk_norm);
+            }
+        }
+        if let Some(squared_magnitude) = single_trait_implementations.get("SquaredMagnitude") {
+            let weight_norm = MultiVectorClass::derive_magnitude("WeightNorm", squared_magnitude, parameter_a);
+            ast_nodes.push(weight_norm.clone());
+            single_trait_implementations.insert(result_of_trait!(weight_norm).name.to_string(), weight_norm);
+        }
+        if let (Some(bulk_norm), Some(weight_norm)) = (
+            single_trait_implementations.get("BulkNorm"),
+            single_trait_implementations.get("WeightNorm"),
+        ) {
+            let geometric_norm = MultiVectorClass::derive_geometric_norm("GeometricNorm", bulk_norm, weight_norm, parameter_a);
+            ast_nodes.push(geometric_norm);
+        }
+    }
+    for (parameter_b, pair_trait_implementations) in pair_trait_implementations.values() {
+        if let Some(geometric_product) = pair_trait_implementations.get("GeometricProduct") {
+            if parameter_b.data_type.is_scalar() {
+                if !parameter_a.data_type.is_scalar() {
+                    let scale = MultiVectorClass::derive_scale("Mul", geometric_product, parameter_a, parameter_b);
+                    ast_nodes.push(scale);
+                }
+                if let Some(magnitude) = single_trait_implementations.get("Magnitude") {
+                    let signum = MultiVectorClass::derive_signum("Signum", geometric_product, magnitude, parameter_a, parameter_b);
+                    ast_nodes.push(signum.clone());
+                    single_trait_implementations.insert(result_of_trait!(signum).name.to_string(), signum);
+                }
+                if let Some(weight_norm) = single_trait_implementations.get("WeightNorm") {
+                    let unitize = MultiVectorClass::derive_signum("Unitize", geometric_product, weight_norm, parameter_a, parameter_b);
+                    ast_nodes.push(unitize);
+                }
+                if let Some(squared_magnitude) = single_trait_implementations.get("SquaredMagnitude") {
+                    // The other classes get the general inverse once the products of all classes are known
+                    if let Some(reversal) = single_trait_implementations
+                        .get("Reversal")
+                        .filter(|_| algebra_descriptor.has_versor_inverse(class_a))
+                    {
+                        let inverse = MultiVectorClass::derive_inverse("Inverse", geometric_product, squared_magnitude, reversal, parameter_a);
+                        ast_nodes.push(inverse.clone());
+                        single_trait_implementations.insert(result_of_trait!(inverse).name.to_string(), inverse);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Inverses of all classes by class name
+///
+/// Elements of the classes which need not be versors, e.g. multivectors, are inverted by their adjugate, see `derive_general_inverse`.
+fn derive_general_inverses<'a>(
+    algebra_descriptor: &AlgebraDescriptor,
+    registry: &'a MultiVectorClassRegistry,
+    scalar_class: Option<&'a MultiVectorClass>,
+    trait_implementations: &TraitImplementations<'a>,
+    ast_nodes: &mut Vec<AstNode<'a>>,
+) -> BTreeMap<String, AstNode<'a>> {
+    let mut inverses = trait_implementations
+        .0
+        .iter()
+        .filter_map(|(class_name, class_implementations)| Some((class_name.clone(), class_implementations.single.get("Inverse")?.clone())))
+        .collect::<BTreeMap<_, _>>();
+    let dimension = algebra_descriptor.algebra().generator_squares.len();
+    let scalar_class = match scalar_class.filter(|_| dimension <= MAX_GENERAL_INVERSE_GENERATORS) {
+        Some(scalar_class) => scalar_class,
+        None => return inverses,
+    };
+    for class_implementations in trait_implementations.0.values() {
+        let parameter_a = &class_implementations.parameter;
+        let class_a = parameter_a.multi_vector_class();
+        if algebra_descriptor.has_versor_inverse(class_a) {
+            continue;
+        }
+        let general_inverse = || {
+            let involution_names: &[&str] = if matches!(dimension, 3 | 5) {
+                &["Conjugation", "Automorphism", "Reversal"]
+            } else {
+                &["Conjugation"]
+            };
+            let involutions = involution_names
+                .iter()
+                .map(|name| class_implementations.single.get(*name))
+                .collect::<Option<Vec<_>>>()?;
+            let mut cofactor_products = Vec::new();
+            let mut cofactor_class = class_a;
+            for _ in 1..involutions.len() {
+                let geometric_product = trait_implementations.pair(cofactor_class, class_a, "GeometricProduct")?;
+                cofactor_class = result_of_trait!(geometric_product).multi_vector_class();
+                cofactor_products.push(geometric_product);
+            }
+            let grade_negation = if dimension >= 4 {
+                let geometric_product = trait_implementations.pair(class_a, cofactor_class, "GeometricProduct")?;
+                let product_class = result_of_trait!(geometric_product).multi_vector_class();
+                Some(GradeNegation {
+                    grades: if dimension == 4 { vec![3, 4] } else { vec![1, 4] },
+                    geometric_product,
+                    grade_select: trait_implementations.single(product_class, "GradeSelect")?,
+                    scale: trait_implementations.pair(product_class, scalar_class, "GeometricProduct")?,
+                    subtraction: trait_implementations.pair(product_class, product_class, "Sub")?,
+                    geometric_product_2: trait_implementations.pair(cofactor_class, product_class, "GeometricProduct")?,
+                })
+            } else {
+                None
+            };
+            let adjugate_class = match &grade_negation {
+                Some(grade_negation) => result_of_trait!(grade_negation.geometric_product_2).multi_vector_class(),
+                None => cofactor_class,
+            };
+            // The automorphism and the reversal fix or negate the inverses of all elements of `class_a` if they do so for `class_a`,
+            // so the grades they would not fix or negate are dropped from the adjugate
+            let grades_a = class_a.signature().iter().map(|index| index.count_ones()).collect::<Vec<_>>();
+            let involution_signs: [fn(u32) -> bool; 2] = [|grade| grade % 2 == 1, |grade| grade % 4 >= 2];
+            let signature = adjugate_class
+                .signature()
+                .into_iter()
+                .filter(|index| {
+                    involution_signs.iter().all(|sign| {
+                        let sign_a = sign(grades_a[0]);
+                        grades_a.iter().any(|grade| sign(*grade) != sign_a) || sign(index.count_ones()) == sign_a
+                    })
+                })
+                .collect::<Vec<_>>();
+            let conversion = match registry.get(&signature) {
+                Some(class) if class != adjugate_class => trait_implementations.pair(adjugate_class, class, "Into"),
+                _ => None,
+            };
+            Some(MultiVectorClass::derive_general_inverse(
+                "Inverse",
+                &involutions,
+                &cofactor_products,
+                grade_negation.as_ref(),
+                trait_implementations.pair(class_a, adjugate_class, "ScalarProduct")?,
+                trait_implementations.pair(adjugate_class, scalar_class, "GeometricProduct")?,
+                conversion,
+                parameter_a,
+            ))
+        };
+        if let Some(inverse) = general_inverse() {
+            ast_nodes.push(inverse.clone());
+            inverses.insert(class_a.class_name.clone(), inverse);
+        }
+    }
+    inverses
+}
+
+/// Integer powers of the classes closed under the geometric product and the geometric quotient
+fn derive_powers_and_quotients<'a>(
+    trait_implementations: &TraitImplementations<'a>,
+    inverses: &BTreeMap<String, AstNode<'a>>,
+    parameter_a: &Parameter<'a>,
+    parameter_b: &Parameter<'a>,
+    ast_nodes: &mut Vec<AstNode<'a>>,
+) {
+    let class_a = parameter_a.multi_vector_class();
+    let class_b = parameter_b.multi_vector_class();
+    let geometric_product = match trait_implementations.pair(class_a, class_b, "GeometricProduct") {
+        Some(geometric_product) => geometric_product,
+        None => return,
+    };
+    if class_a == class_b && result_of_trait!(geometric_product).multi_vector_class() == class_a {
+        if let (Some(constant_one), Some(inverse)) = (trait_implementations.single(class_a, "One"), inverses.get(&class_a.class_name)) {
+            let power_of_integer = MultiVectorClass::derive_power_of_integer(
+                "Powi",
+                geometric_product,
+                constant_one,
+                inverse,
+                parameter_a,
+                &Parameter {
+                    name: "exponent",
+                    data_type: DataType::Integer,
+                },
+            );
+            ast_nodes.push(power_of_integer);
+        }
+    }
+    if let Some(inverse) = inverses.get(&class_b.class_name) {
+        let division = MultiVectorClass::derive_division("GeometricQuotient", geometric_product, inverse, parameter_a, parameter_b);
+        ast_nodes.push(division);
+    }
+}
+
+/// Sandwich products of `parameter_b` with the versor `parameter_a`, see `SANDWICH_PRODUCTS`
+fn derive_sandwich_products<'a>(
+    algebra_descriptor: &AlgebraDescriptor,
+    registry: &'a MultiVectorClassRegistry,
+    trait_implementations: &TraitImplementations<'a>,
+    parameter_a: &Parameter<'a>,
+    parameter_b: &Parameter<'a>,
+    ast_nodes: &mut Vec<AstNode<'a>>,
+) {
+    let class_a = parameter_a.multi_vector_class();
+    let class_b = parameter_b.multi_vector_class();
+    // The first product of a versor with a class may have no class, e.g. a translator multiplied by a sphere of conformal geometric algebra,
+    // so versors are converted into the classes of the same parity containing them, as long as the result converts back into `class_b`
+    let widenings = widenings(registry, trait_implementations, class_a)
+        .into_iter()
+        .filter(|(class, widening)| widening.is_none() || (class.parity() == class_a.parity() && algebra_descriptor.has_versor_inverse(class_a)))
+        .collect::<Vec<_>>();
+    for (name, product_name, involution_name) in SANDWICH_PRODUCTS.iter() {
+        // The anti reversal of a scalar depends on the dimension of the algebra, so f32 can not implement it
+        if *involution_name == "AntiReversal" && parameter_a.data_type.is_scalar() {
+            continue;
+        }
+        let sandwich_product = widenings.iter().find_map(|(class, widening)| {
+            let geometric_product = trait_implementations.pair(class, class_b, product_name)?;
+            let involution = trait_implementations.single(class, involution_name)?;
+            let geometric_product_2 = trait_implementations.pair(result_of_trait!(geometric_product).multi_vector_class(), class, product_name)?;
+            let result_class = result_of_trait!(geometric_product_2).multi_vector_class();
+            let conversion = trait_implementations.pair(result_class, class_b, "Into");
+            if widening.is_some() && result_class != class_b && conversion.is_none() {
+                return None;
+            }
+            Some(MultiVectorClass::derive_sandwich_product(
+                name,
+                geometric_product,
+                geometric_product_2,
+                involution,
+                *widening,
+                conversion,
+                parameter_a,
+                parameter_b,
+            ))
+        });
+        if let Some(sandwich_product) = sandwich_product {
+            ast_nodes.push(sandwich_product);
+        }
+    }
+}
+
+/// Projections of `parameter_a` onto `parameter_b` and its rejection and reflection, see `PROJECTIONS`
+fn derive_projections<'a>(
+    registry: &'a MultiVectorClassRegistry,
+    trait_implementations: &TraitImplementations<'a>,
+    inverses: &BTreeMap<String, AstNode<'a>>,
+    parameter_a: &Parameter<'a>,
+    parameter_b: &Parameter<'a>,
+    ast_nodes: &mut Vec<AstNode<'a>>,
+) {
+    let class_a = parameter_a.multi_vector_class();
+    let class_b = parameter_b.multi_vector_class();
+    let inverse = match inverses.get(&class_b.class_name) {
+        Some(inverse) => inverse,
+        None => return,
+    };
+    let inverse_class = result_of_trait!(inverse).multi_vector_class();
+    // Results which are neither of the class of `parameter_a` nor convertible to it are dropped
+    let conversion_to_a = |ast_node: &AstNode| {
+        let class = result_of_trait!(ast_node).multi_vector_class();
+        if class == class_a {
+            Some(None)
+        } else {
+            trait_implementations.pair(class, class_a, "Into").map(Some)
+        }
+    };
+    // The class of `parameter_a` may lack a product which a class containing it has, e.g. a point multiplied by a plane
+    let widenings = widenings(registry, trait_implementations, class_a);
+    let mut projections = BTreeMap::new();
+    for (name, product_name) in PROJECTIONS.iter() {
+        let projection = widenings.iter().find_map(|(class, widening)| {
+            let product = trait_implementations.pair(class, class_b, product_name)?;
+            let geometric_product = trait_implementations.pair(result_of_trait!(product).multi_vector_class(), inverse_class, "GeometricProduct")?;
+            let conversion = conversion_to_a(geometric_product)?;
+            Some(MultiVectorClass::derive_projection(
+                name,
+                product,
+                geometric_product,
+                inverse,
+                *widening,
+                conversion,
+                parameter_a,
+                parameter_b,
+            ))
+        });
+        if let Some(projection) = projection {
+            ast_nodes.push(projection.clone());
+            projections.insert(*name, projection);
+        }
+    }
+    // The outer product of a point and a plane of a projective geometric algebra is the pseudoscalar,
+    // whose product with the inverse of the plane has no class, so the rejection is the point minus its projection instead.
+    // Both products contain the product of a scalar and a vector, so this needs classes without a scalar.
+    let is_vector = |class: &MultiVectorClass| class.signature().iter().all(|index| index.count_ones() == 1);
+    let has_scalar = |class: &MultiVectorClass| class.signature().contains(&0);
+    if (is_vector(class_a) || is_vector(class_b)) && !has_scalar(class_a) && !has_scalar(class_b) {
+        if let Some(subtraction) = trait_implementations.pair(class_a, class_a, "Sub") {
+            for ((name, _), (other_name, _)) in PROJECTIONS.iter().zip(PROJECTIONS.iter().rev()) {
+                if let (None, Some(projection)) = (projections.get(name), projections.get(other_name)) {
+                    ast_nodes.push(MultiVectorClass::derive_projection_remainder(
+                        name,
+                        projection,
+                        subtraction,
+                        parameter_a,
+                        parameter_b,
+                    ));
+                }
+            }
+        }
+    }
+    // Odd versors reflect with the opposite orientation, which the automorphism of `parameter_a` compensates
+    if let Some(parity) = class_b.parity() {
+        let automorphism = if parity == 1 {
+            trait_implementations.single(class_a, "Automorphism")
+        } else {
+            None
+        };
+        if parity == 0 || automorphism.is_some() {
+            let reflection = widenings.iter().find_map(|(class, widening)| {
+                let geometric_product = trait_implementations.pair(class_b, class, "GeometricProduct")?;
+                let geometric_product_2 = trait_implementations.pair(
+                    result_of_trait!(geometric_product).multi_vector_class(),
+                    inverse_class,
+                    "GeometricProduct",
+                )?;
+                let conversion = conversion_to_a(geometric_product_2)?;
+                Some(MultiVectorClass::derive_reflection(
+                    "ReflectIn",
+                    geometric_product,
+                    geometric_product_2,
+                    automorphism,
+                    inverse,
+                    *widening,
+                    conversion,
+                    parameter_a,
+                    parameter_b,
+                ))
+            });
+            if let Some(reflection) = reflection {
+                ast_nodes.push(reflection);
+            }
+        }
+    }
+}
+
+/// The classes containing `class_a` from the smallest up, starting with `class_a` itself, and the conversions of `class_a` into them
+fn widenings<'a, 'b>(
+    registry: &'a MultiVectorClassRegistry,
+    trait_implementations: &'b TraitImplementations<'a>,
+    class_a: &'a MultiVectorClass,
+) -> Vec<(&'a MultiVectorClass, Option<&'b AstNode<'a>>)> {
+    let signature_a = class_a.signature();
+    let mut widenings = registry
+        .classes
+        .iter()
+        .filter(|class| signature_a.iter().all(|index| class.signature().contains(index)))
+        .filter_map(|class| {
+            if class == class_a {
+                Some((class, None))
+            } else {
+                trait_implementations.pair(class_a, class, "Into").map(|widening| (class, Some(widening)))
+            }
+        })
+        .collect::<Vec<_>>();
+    widenings.sort_by_key(|(class, _widening)| class.signature().len());
+    widenings
+}
+
+/// Exponentials of the bivector classes by class name
+///
+/// Bivectors of more than two commuting simple parts are left out, as their squares would be the roots of a cubic.
+fn derive_exponentials<'a>(
+    registry: &'a MultiVectorClassRegistry,
+    scalar_class: &'a MultiVectorClass,
+    trait_implementations: &TraitImplementations<'a>,
+    ast_nodes: &mut Vec<AstNode<'a>>,
+) -> BTreeMap<String, AstNode<'a>> {
+    let mut exponentials = BTreeMap::new();
+    for class_implementations in trait_implementations.0.values() {
+        let parameter_a = &class_implementations.parameter;
+        let class_a = parameter_a.multi_vector_class();
+        let signature_a = class_a.signature();
+        let bivector_rank = class_a.bivector_rank();
+        if signature_a.iter().any(|index| index.count_ones() != 2) || !(1..=2).contains(&bivector_rank) {
+            continue;
+        }
+        // The exponential has a scalar part and the products of the commuting parts
+        let mut signature = vec![0];
+        for index_a in signature_a.iter() {
+            signature.push(*index_a);
+            signature.extend(
+                signature_a
+                    .iter()
+                    .filter(|index_b| *index_a & **index_b == 0)
+                    .map(|index_b| index_a | index_b),
+            );
+        }
+        let exponential = registry
+            .classes
+            .iter()
+            .filter(|class| signature.iter().all(|index| class.signature().contains(index)))
+            .filter_map(|class| {
+                let geometric_product = trait_implementations.pair(class, class, "GeometricProduct")?;
+                if result_of_trait!(geometric_product).multi_vector_class() != class {
+                    return None;
+                }
+                Some(MultiVectorClass::derive_exponential(
+                    "Exp",
+                    trait_implementations.pair(class_a, class, "Into")?,
+                    geometric_product,
+                    trait_implementations.pair(class, scalar_class, "GeometricProduct")?,
+                    trait_implementations.pair(class, scalar_class, "Add")?,
+                    trait_implementations.pair(class, class, "Add")?,
+                    bivector_rank,
+                    parameter_a,
+                ))
+            })
+            .min_by_key(|exponential| result_of_trait!(exponential).multi_vector_class().signature().len());
+        if let Some(exponential) = exponential {
+            ast_nodes.push(exponential.clone());
+            exponentials.insert(class_a.class_name.clone(), exponential);
+        }
+    }
+    exponentials
+}
+
+/// Logarithms of the even classes and powers, which take a logarithm and an exponential
+fn derive_logarithms<'a>(
+    registry: &'a MultiVectorClassRegistry,
+    scalar_class: &'a MultiVectorClass,
+    trait_implementations: &TraitImplementations<'a>,
+    exponentials: &BTreeMap<String, AstNode<'a>>,
+    ast_nodes: &mut Vec<AstNode<'a>>,
+) {
+    for class_implementations in trait_implementations.0.values() {
+        let parameter_a = &class_implementations.parameter;
+        let class_a = parameter_a.multi_vector_class();
+        let signature_a = class_a.signature();
+        if class_a.parity() != Some(0) || !signature_a.contains(&0) || signature_a.iter().any(|index| index.count_ones() > 4) {
+            continue;
+        }
+        let bivector_signature = signature_a.iter().filter(|index| index.count_ones() == 2).cloned().collect::<Vec<_>>();
+        let bivector_class = match registry.get(&bivector_signature) {
+            Some(bivector_class) => bivector_class,
+            None => continue,
+        };
+        let bivector_rank = bivector_class.bivector_rank();
+        if bivector_rank > 2 {
+            continue;
+        }
+        let logarithm = || {
+            let geometric_product = trait_implementations.pair(class_a, class_a, "GeometricProduct")?;
+            if result_of_trait!(geometric_product).multi_vector_class() != class_a {
+                return None;
+            }
+            // The bivector part is widened back into `class_a` to split it
+            trait_implementations.pair(bivector_class, class_a, "Into")?;
+            Some(MultiVectorClass::derive_logarithm(
+                "Ln",
+                trait_implementations.pair(class_a, bivector_class, "Into")?,
+                geometric_product,
+                trait_implementations.pair(class_a, scalar_class, "GeometricProduct")?,
+                trait_implementations.pair(class_a, class_a, "Add")?,
+                trait_implementations.pair(class_a, class_a, "Sub")?,
+                bivector_rank,
+                parameter_a,
+            ))
+        };
+        if let Some(logarithm) = logarithm() {
+            ast_nodes.push(logarithm.clone());
+            if let (Some(exponential), Some(scale)) = (
+                exponentials.get(&bivector_class.class_name),
+                trait_implementations.pair(bivector_class, scalar_class, "GeometricProduct"),
+            ) {
+                if result_of_trait!(exponential).multi_vector_class() == class_a {
+                    let power_of_float = MultiVectorClass::derive_power_of_float(
+                        "Powf",
+                        &logarithm,
+                        scale,
+                        exponential,
+                        parameter_a,
+                        &Parameter {
+                            name: "exponent",
+                            data_type: DataType::SimdVector(1),
+                        },
+                    );
+                    ast_nodes.push(power_of_float);
+                }
+            }
+        }
+    }
+}
+
+/// Exponentials, logarithms and powers of the classes of a scalar and a simple bivector, whose bivector part has no class
+///
+/// These stay in their class like the functions of the complex numbers, so the logarithm has the logarithm of the magnitude as its scalar part.
+fn derive_complex_functions<'a>(
+    registry: &'a MultiVectorClassRegistry,
+    scalar_class: &'a MultiVectorClass,
+    trait_implementations: &TraitImplementations<'a>,
+    ast_nodes: &mut Vec<AstNode<'a>>,
+) {
+    for class_implementations in trait_implementations.0.values() {
+        let parameter_a = &class_implementations.parameter;
+        let class_a = parameter_a.multi_vector_class();
+        let signature_a = class_a.signature();
+        let bivector_signature = signature_a.iter().filter(|index| index.count_ones() == 2).cloned().collect::<Vec<_>>();
+        if !signature_a.contains(&0)
+            || bivector_signature.len() + 1 != signature_a.len()
+            || class_a.bivector_rank() != 1
+            || registry.get(&bivector_signature).is_some()
+        {
+            continue;
+        }
+        let functions = || {
+            let geometric_product = trait_implementations.pair(class_a, class_a, "GeometricProduct")?;
+            if result_of_trait!(geometric_product).multi_vector_class() != class_a {
+                return None;
+            }
+            let subtraction = trait_implementations.pair(class_a, scalar_class, "Sub")?;
+            let scale = trait_implementations.pair(class_a, scalar_class, "GeometricProduct")?;
+            let scalar_addition = trait_implementations.pair(class_a, scalar_class, "Add")?;
+            let exponential =
+                MultiVectorClass::derive_complex_exponential("Exp", subtraction, geometric_product, scale, scalar_addition, parameter_a);
+            let logarithm = MultiVectorClass::derive_complex_logarithm(
+                "Ln",
+                trait_implementations.single(class_a, "Magnitude")?,
+                subtraction,
+                geometric_product,
+                scale,
+                scalar_addition,
+                parameter_a,
+            );
+            let power_of_float = MultiVectorClass::derive_power_of_float(
+                "Powf",
+                &logarithm,
+                scale,
+                &exponential,
+                parameter_a,
+                &Parameter {
+                    name: "exponent",
+                    data_type: DataType::SimdVector(1),
+                },
+            );
+            Some([exponential, logarithm, power_of_float])
+        };
+        if let Some(functions) = functions() {
+            ast_nodes.extend(functions);
+        }
+    }
+}
+
+/// Square roots of the even versor classes
+///
+/// The sum of a versor and its reversal has the elements the reversal fixes, which are the scalar and a quadvector commuting with the class,
+/// like the pseudoscalar of a four dimensional algebra. Classes with more quadvectors, like all even versors of a five dimensional algebra, are left out.
+fn derive_square_roots<'a>(
+    algebra_descriptor: &AlgebraDescriptor,
+    scalar_class: &'a MultiVectorClass,
+    trait_implementations: &TraitImplementations<'a>,
+    ast_nodes: &mut Vec<AstNode<'a>>,
+) {
+    let algebra = algebra_descriptor.algebra();
+    for class_implementations in trait_implementations.0.values() {
+        let parameter_a = &class_implementations.parameter;
+        let class_a = parameter_a.multi_vector_class();
+        let basis_a = class_a.flat_basis();
+        if class_a.parity() != Some(0) || !class_a.signature().contains(&0) || class_a.is_scalar() {
+            continue;
+        }
+        let quadvector = match basis_a
+            .iter()
+            .filter(|element| element.index != 0 && element.grade() % 4 == 0)
+            .collect::<Vec<_>>()
+            .as_slice()
+        {
+            [] => None,
+            [quadvector] if quadvector.grade() == 4 => {
+                let square = algebra.product(quadvector, quadvector);
+                if square.iter().any(|element| element.index != 0)
+                    || basis_a
+                        .iter()
+                        .any(|element| algebra.product(quadvector, element) != algebra.product(element, quadvector))
+                {
+                    continue;
+                }
+                Some((quadvector.index, square.iter().map(|element| element.scalar).sum::<isize>()))
+            }
+            _ => continue,
+        };
+        let square_root = || {
+            let geometric_product = trait_implementations.pair(class_a, class_a, "GeometricProduct")?;
+            if result_of_trait!(geometric_product).multi_vector_class() != class_a {
+                return None;
+            }
+            Some(MultiVectorClass::derive_square_root(
+                "Sqrt",
+                class_implementations.single.get("Magnitude")?,
+                geometric_product,
+                class_implementations.single.get("Reversal")?,
+                trait_implementations.pair(class_a, class_a, "Add")?,
+                trait_implementations.pair(class_a, scalar_class, "Add")?,
+                trait_implementations.pair(class_a, scalar_class, "GeometricProduct")?,
+                quadvector,
+                parameter_a,
+            ))
+        };
+        if let Some(square_root) = square_root() {
+            ast_nodes.push(square_root);
+        }
+    }
+}
+
+/// Identifies a trait implementation by its trait, parameter types and result type
+fn trait_key(name: &str, parameters: &[DataType], result: &DataType) -> String {
+    let type_name = |data_type: &DataType| match data_type {
+        DataType::Integer => "isize".to_string(),
+        DataType::GradeMask => "u128".to_string(),
+        DataType::SimdVector(size) => format!("Simd32x{}", size),
+        DataType::MultiVector(class) => class.class_name.clone(),
+    };
+    format!(
+        "{}({}) -> {}",
+        name,
+        parameters.iter().map(type_name).collect::<Vec<_>>().join(", "),
+        type_name(result)
+    )
+}
+
+/// Marks the trait implementations selected in the descriptor and the ones they invoke
+fn select_trait_implementations(algebra_descriptor: &AlgebraDescriptor, ast_nodes: &[AstNode]) -> Vec<bool> {
+    let mut selected = vec![false; ast_nodes.len()];
+    let mut pending = Vec::new();
+    let mut index_by_key = std::collections::HashMap::new();
+    for (index, ast_node) in ast_nodes.iter().enumerate() {
+        if let AstNode::TraitImplementation { result, parameters, .. } = ast_node {
+            let parameter_data_types = parameters.iter().map(|parameter| parameter.data_type.clone()).collect::<Vec<_>>();
+            index_by_key.insert(trait_key(result.name, &parameter_data_types, &result.data_type), index);
+            let class_a = parameters.first().unwrap_or(result).multi_vector_class();
+            let class_b = match parameters.get(1) {
+                Some(Parameter {
+                    data_type: DataType::MultiVector(class_b),
+                    ..
+                }) => Some(class_b.class_name.as_str()),
+                _ => None,
+            };
+            if algebra_descriptor.is_trait_selected(result.name, &class_a.class_name, class_b) {
+                pending.push(index);
+            }
+        }
+    }
+    while let Some(index) = pending.pop() {
+        if selected[index] {
+            continue;
+        }
+        selected[index] = true;
+        let mut invocations = Vec::new();
+        ast_nodes[index].collect_invocations(&mut invocations);
+        for (name, parameters, result) in invocations.iter() {
+            if let Some(dependency) = index_by_key.get(&trait_key(name, parameters, result)) {
+                pending.push(*dependency);
+            }
+        }
+    }
+    selected
+}
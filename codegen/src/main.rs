@@ -1,229 +1,253 @@
-mod algebra;
-mod ast;
-mod compile;
-mod emit;
-mod glsl;
-mod rust;
-
-use crate::{
-    algebra::{BasisElement, GeometricAlgebra, Involution, MultiVectorClass, MultiVectorClassRegistry, Product},
-    ast::{AstNode, DataType, Parameter},
-    emit::Emitter,
+use codegen::{
+    coverage::TraitCoverage,
+    dsl::AlgebraDescriptor,
+    emit::{element_label, Backend, Emitter},
+    tables::{product_label, AlgebraTables},
 };
 
+const USAGE: &str = "usage: codegen [OPTIONS] (--config FILE | DESCRIPTOR)
+
+options:
+    -h, --help          print this help and exit
+    --config FILE       read the algebras from a descriptor file
+    --output DIRECTORY  directory to write the generated files to (default: ../src/)
+    --emit BACKENDS     comma separated list of backends to emit: rust, glsl (default: rust,glsl)
+    --quiet             do not print the Cayley table of each algebra
+    --report-missing    list the products which are dropped because no class matches their result,
+                        the classes without an inverse because their elements need not be versors
+                        and the pairs of classes which have a projection but no rejection or vice versa
+    --synthesize        add a class for the result of every dropped product
+    --coverage          also write <name>.coverage.json and <name>.coverage.md listing the implemented traits
+    --tables            also write <name>.tables.json, <name>.tables.csv and <name>.tables.tex with the product and involution tables";
+
+/// Larger Cayley tables, also those written by `--tables`, would have more than 1024 columns
+const MAX_CAYLEY_TABLE_GENERATORS: usize = 10;
+
+struct Options {
+    help: bool,
+    config: Option<String>,
+    descriptor: Option<String>,
+    output_directory: std::path::PathBuf,
+    backends: Vec<Backend>,
+    quiet: bool,
+    report_missing: bool,
+    synthesize: bool,
+    coverage: bool,
+    tables: bool,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self {
+            help: false,
+            config: None,
+            descriptor: None,
+            output_directory: std::path::PathBuf::from("../src/"),
+            backends: vec![Backend::Rust, Backend::Glsl],
+            quiet: false,
+            report_missing: false,
+            synthesize: false,
+            coverage: false,
+            tables: false,
+        };
+        while let Some(arg) = args.next() {
+            let mut value = |option: &str| args.next().ok_or_else(|| format!("missing value for {}", option));
+            match arg.as_str() {
+                "--help" | "-h" => {
+                    options.help = true;
+                    return Ok(options);
+                }
+                "--config" => options.config = Some(value("--config")?),
+                "--output" => options.output_directory = std::path::PathBuf::from(value("--output")?),
+                "--emit" => {
+                    options.backends = value("--emit")?
+                        .split(',')
+                        .map(|name| match name.trim() {
+                            "rust" => Ok(Backend::Rust),
+                            "glsl" => Ok(Backend::Glsl),
+                            name => Err(format!("unknown backend `{}`", name)),
+                        })
+                        .collect::<Result<Vec<_>, _>>()?
+                }
+                "--quiet" => options.quiet = true,
+                "--report-missing" => options.report_missing = true,
+                "--synthesize" => options.synthesize = true,
+                "--coverage" => options.coverage = true,
+                "--tables" => options.tables = true,
+                _ if arg.starts_with("--") => return Err(format!("unknown option `{}`", arg)),
+                _ if options.descriptor.is_some() => return Err(format!("unexpected argument `{}`", arg)),
+                _ => options.descriptor = Some(arg),
+            }
+        }
+        if options.config.is_some() == options.descriptor.is_some() {
+            return Err("expected either --config FILE or a DESCRIPTOR".to_string());
+        }
+        Ok(options)
+    }
+}
+
 fn main() {
-    let mut args = std::env::args();
-    let _executable = args.next().unwrap();
-    let config = args.next().unwrap();
-    let mut config_iter = config.split(';');
-    let algebra_descriptor = config_iter.next().unwrap();
-    let mut algebra_descriptor_iter = algebra_descriptor.split(':');
-    let algebra_name = algebra_descriptor_iter.next().unwrap();
-    let generator_squares = algebra_descriptor_iter
-        .next()
-        .unwrap()
-        .split(',')
-        .map(|x| x.parse::<isize>().unwrap())
-        .collect::<Vec<_>>();
-    let algebra = GeometricAlgebra {
-        generator_squares: generator_squares.as_slice(),
+    let options = Options::parse(std::env::args().skip(1)).unwrap_or_else(|error| {
+        eprintln!("{}\n\n{}", error, USAGE);
+        std::process::exit(1);
+    });
+    if options.help {
+        println!("{}", USAGE);
+        return;
+    }
+    let (source_name, parsed) = if let Some(path) = &options.config {
+        let source = std::fs::read_to_string(path).unwrap_or_else(|error| {
+            eprintln!("{}: {}", path, error);
+            std::process::exit(1);
+        });
+        (path.clone(), AlgebraDescriptor::parse_file(&source))
+    } else {
+        (
+            "<argument>".to_string(),
+            AlgebraDescriptor::parse_inline(options.descriptor.as_ref().unwrap()).map(|algebra_descriptor| vec![algebra_descriptor]),
+        )
     };
-    let involutions = Involution::involutions(&algebra);
-    let products = Product::products(&algebra);
-    let basis = algebra.sorted_basis();
-    for b in basis.iter() {
-        for a in basis.iter() {
-            print!("{:1$} ", BasisElement::product(a, b, &algebra), generator_squares.len() + 2);
+    let mut algebra_descriptors = parsed.unwrap_or_else(|errors| {
+        for error in errors.iter() {
+            eprintln!("{}:{}", source_name, error);
         }
-        println!();
+        eprintln!("{} error(s) in algebra descriptor", errors.len());
+        std::process::exit(1);
+    });
+    if let Err(error) = std::fs::create_dir_all(&options.output_directory) {
+        eprintln!("{}: {}", options.output_directory.display(), error);
+        std::process::exit(1);
     }
-    let mut registry = MultiVectorClassRegistry::default();
-    for multi_vector_descriptor in config_iter {
-        let mut multi_vector_descriptor_iter = multi_vector_descriptor.split(':');
-        registry.register(MultiVectorClass {
-            class_name: multi_vector_descriptor_iter.next().unwrap().to_owned(),
-            grouped_basis: multi_vector_descriptor_iter
-                .next()
-                .unwrap()
-                .split('|')
-                .map(|group_descriptor| {
-                    group_descriptor
-                        .split(',')
-                        .map(|element_name| BasisElement::parse(element_name, &algebra))
-                        .collect::<Vec<_>>()
-                })
-                .collect::<Vec<_>>(),
-        });
+    for algebra_descriptor in algebra_descriptors.iter_mut() {
+        if options.report_missing {
+            report_missing_products(algebra_descriptor);
+        }
+        if options.synthesize {
+            synthesize_missing_classes(algebra_descriptor, &options);
+        }
+        generate(algebra_descriptor, &options);
     }
-    let mut emitter = Emitter::new(&std::path::Path::new("../src/").join(std::path::Path::new(algebra_name)));
-    emitter.emit(&AstNode::Preamble).unwrap();
-    for class in registry.classes.iter() {
-        emitter.emit(&AstNode::ClassDefinition { class }).unwrap();
+}
+
+fn report_missing_products(algebra_descriptor: &AlgebraDescriptor) {
+    let algebra = algebra_descriptor.algebra();
+    for missing in algebra_descriptor.missing_products() {
+        let element_names = algebra
+            .sorted_elements(&missing.signature)
+            .iter()
+            .map(|element| element.name(&algebra_descriptor.generator_names))
+            .collect::<Vec<_>>();
+        println!(
+            "{}: {} {} {} has no result class with {}",
+            algebra_descriptor.name,
+            missing.class_a,
+            missing.product,
+            missing.class_b,
+            element_names.join(", ")
+        );
     }
-    let mut trait_implementations = std::collections::BTreeMap::new();
-    for class_a in registry.classes.iter() {
-        let parameter_a = Parameter {
-            name: "self",
-            data_type: DataType::MultiVector(class_a),
-        };
-        let mut single_trait_implementations = std::collections::BTreeMap::new();
-        for name in &["Zero", "One"] {
-            let ast_node = class_a.constant(name);
-            emitter.emit(&ast_node).unwrap();
-            if ast_node != AstNode::None {
-                single_trait_implementations.insert(name.to_string(), ast_node);
+    for class_name in algebra_descriptor.classes_without_inverse() {
+        println!(
+            "{}: {} has no inverse, because its elements need not be versors and there are more than {} generators",
+            algebra_descriptor.name,
+            class_name,
+            codegen::MAX_GENERAL_INVERSE_GENERATORS
+        );
+    }
+    match codegen::missing_projections(algebra_descriptor) {
+        Ok(missing_projections) => {
+            for (name, class_a, class_b) in missing_projections {
+                println!(
+                    "{}: {} {} {} is missing, because an intermediate product has no class",
+                    algebra_descriptor.name, class_a, name, class_b
+                );
             }
         }
-        for (name, involution) in involutions.iter() {
-            let ast_node = MultiVectorClass::involution(name, involution, &parameter_a, &registry, false);
-            emitter.emit(&ast_node).unwrap();
-            if ast_node != AstNode::None {
-                single_trait_implementations.insert(name.to_string(), ast_node);
-            }
+        Err(error) => eprintln!("{}: {}", algebra_descriptor.name, error),
+    }
+}
+
+fn synthesize_missing_classes(algebra_descriptor: &mut AlgebraDescriptor, options: &Options) {
+    for class_index in algebra_descriptor.synthesize_missing_classes() {
+        if options.quiet {
+            continue;
         }
-        let mut pair_trait_implementations = std::collections::BTreeMap::new();
-        for class_b in registry.classes.iter() {
-            let mut trait_implementations = std::collections::BTreeMap::new();
-            let parameter_b = Parameter {
-                name: "other",
-                data_type: DataType::MultiVector(class_b),
-            };
-            if class_a != class_b {
-                let name = "Into";
-                let ast_node = MultiVectorClass::involution(name, &Involution::projection(class_b), &parameter_a, &registry, true);
-                emitter.emit(&ast_node).unwrap();
-                if ast_node != AstNode::None {
-                    trait_implementations.insert(name.to_string(), ast_node);
-                }
-            }
-            for name in &["Add", "Sub"] {
-                let ast_node = MultiVectorClass::element_wise(*name, &parameter_a, &parameter_b, &registry);
-                emitter.emit(&ast_node).unwrap();
-                if ast_node != AstNode::None {
-                    trait_implementations.insert(name.to_string(), ast_node);
-                }
-            }
-            if class_a == class_b {
-                for name in &["Mul", "Div"] {
-                    let ast_node = MultiVectorClass::element_wise(*name, &parameter_a, &parameter_b, &registry);
-                    emitter.emit(&ast_node).unwrap();
-                    if ast_node != AstNode::None {
-                        trait_implementations.insert(name.to_string(), ast_node);
-                    }
-                }
-            }
-            for (name, product) in products.iter() {
-                let ast_node = MultiVectorClass::product(name, product, &parameter_a, &parameter_b, &registry);
-                emitter.emit(&ast_node).unwrap();
-                if ast_node != AstNode::None {
-                    trait_implementations.insert(name.to_string(), ast_node);
-                }
+        let class = &algebra_descriptor.classes[class_index];
+        let groups = class
+            .grouped_basis
+            .iter()
+            .map(|group| {
+                group
+                    .iter()
+                    .map(|element| element_label(element, &algebra_descriptor.generator_names))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .collect::<Vec<_>>();
+        println!("{}: synthesized {}: {}", algebra_descriptor.name, class.class_name, groups.join(" | "));
+    }
+}
+
+fn generate(algebra_descriptor: &AlgebraDescriptor, options: &Options) {
+    if !options.quiet && algebra_descriptor.generator_squares.len() > MAX_CAYLEY_TABLE_GENERATORS {
+        println!(
+            "{}: Cayley table omitted for {} generators",
+            algebra_descriptor.name,
+            algebra_descriptor.generator_squares.len()
+        );
+    } else if !options.quiet {
+        let algebra = algebra_descriptor.algebra();
+        let basis = algebra.sorted_basis();
+        let table = basis
+            .iter()
+            .map(|b| {
+                basis
+                    .iter()
+                    .map(|a| product_label(&algebra.product(a, b), algebra.generator_names))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let width = basis
+            .iter()
+            .map(|element| element.name(algebra.generator_names).len() + 1)
+            .chain(table.iter().flatten().map(|label| label.len()))
+            .max()
+            .unwrap();
+        for row in table.iter() {
+            for label in row.iter() {
+                print!("{:>1$} ", label, width);
             }
-            pair_trait_implementations.insert(
-                parameter_b.multi_vector_class().class_name.clone(),
-                (parameter_b.clone(), trait_implementations),
-            );
+            println!();
         }
-        for (parameter_b, pair_trait_implementations) in pair_trait_implementations.values() {
-            if let Some(scalar_product) = pair_trait_implementations.get("ScalarProduct") {
-                if let Some(reversal) = single_trait_implementations.get("Reversal") {
-                    if parameter_a.multi_vector_class() == parameter_b.multi_vector_class() {
-                        let squared_magnitude =
-                            MultiVectorClass::derive_squared_magnitude("SquaredMagnitude", scalar_product, reversal, &parameter_a);
-                        emitter.emit(&squared_magnitude).unwrap();
-                        let magnitude = MultiVectorClass::derive_magnitude("Magnitude", &squared_magnitude, &parameter_a);
-                        emitter.emit(&magnitude).unwrap();
-                        single_trait_implementations.insert(result_of_trait!(squared_magnitude).name.to_string(), squared_magnitude);
-                        single_trait_implementations.insert(result_of_trait!(magnitude).name.to_string(), magnitude);
-                    }
-                }
-            }
+    }
+    let path = options.output_directory.join(std::path::Path::new(&algebra_descriptor.name));
+    let result = Emitter::new(&path, &options.backends).and_then(|mut emitter| {
+        if options.coverage {
+            emitter.trait_coverage = Some(TraitCoverage::new(algebra_descriptor));
         }
-        for (parameter_b, pair_trait_implementations) in pair_trait_implementations.values() {
-            if let Some(geometric_product) = pair_trait_implementations.get("GeometricProduct") {
-                if parameter_b.data_type.is_scalar() {
-                    if !parameter_a.data_type.is_scalar() {
-                        let scale = MultiVectorClass::derive_scale("Mul", geometric_product, &parameter_a, parameter_b);
-                        emitter.emit(&scale).unwrap();
-                    }
-                    if let Some(magnitude) = single_trait_implementations.get("Magnitude") {
-                        let signum = MultiVectorClass::derive_signum("Signum", geometric_product, magnitude, &parameter_a);
-                        emitter.emit(&signum).unwrap();
-                        single_trait_implementations.insert(result_of_trait!(signum).name.to_string(), signum);
-                    }
-                    if let Some(squared_magnitude) = single_trait_implementations.get("SquaredMagnitude") {
-                        if let Some(reversal) = single_trait_implementations.get("Reversal") {
-                            let inverse = MultiVectorClass::derive_inverse("Inverse", geometric_product, squared_magnitude, reversal, &parameter_a);
-                            emitter.emit(&inverse).unwrap();
-                            single_trait_implementations.insert(result_of_trait!(inverse).name.to_string(), inverse);
-                        }
-                    }
-                }
-            }
+        codegen::generate(algebra_descriptor, "crate", &mut emitter)?;
+        if let Some(trait_coverage) = &emitter.trait_coverage {
+            trait_coverage.write_json(&mut std::fs::File::create(path.with_extension("coverage.json"))?)?;
+            trait_coverage.write_markdown(&mut std::fs::File::create(path.with_extension("coverage.md"))?)?;
         }
-        trait_implementations.insert(
-            parameter_a.multi_vector_class().class_name.clone(),
-            (parameter_a.clone(), single_trait_implementations, pair_trait_implementations),
-        );
-    }
-    for (parameter_a, single_trait_implementations, pair_trait_implementations) in trait_implementations.values() {
-        for (parameter_b, pair_trait_implementations) in pair_trait_implementations.values() {
-            if let Some(geometric_product) = pair_trait_implementations.get("GeometricProduct") {
-                let geometric_product_result = result_of_trait!(geometric_product);
-                if parameter_a.multi_vector_class() == parameter_b.multi_vector_class()
-                    && geometric_product_result.multi_vector_class() == parameter_a.multi_vector_class()
-                {
-                    if let Some(constant_one) = single_trait_implementations.get("One") {
-                        if let Some(inverse) = single_trait_implementations.get("Inverse") {
-                            let power_of_integer = MultiVectorClass::derive_power_of_integer(
-                                "Powi",
-                                geometric_product,
-                                constant_one,
-                                inverse,
-                                parameter_a,
-                                &Parameter {
-                                    name: "exponent",
-                                    data_type: DataType::Integer,
-                                },
-                            );
-                            emitter.emit(&power_of_integer).unwrap();
-                        }
-                    }
-                }
-                if let Some(b_trait_implementations) = trait_implementations.get(&parameter_b.multi_vector_class().class_name) {
-                    if let Some(inverse) = b_trait_implementations.1.get("Inverse") {
-                        let division = MultiVectorClass::derive_division("GeometricQuotient", geometric_product, inverse, parameter_a, parameter_b);
-                        emitter.emit(&division).unwrap();
-                    }
-                }
-                if let Some(reversal) = single_trait_implementations.get("Reversal") {
-                    if let Some(b_trait_implementations) = trait_implementations.get(&geometric_product_result.multi_vector_class().class_name) {
-                        if let Some(b_pair_trait_implementations) = b_trait_implementations.2.get(&parameter_a.multi_vector_class().class_name) {
-                            if let Some(geometric_product_2) = b_pair_trait_implementations.1.get("GeometricProduct") {
-                                let geometric_product_2_result = result_of_trait!(geometric_product_2);
-                                if let Some(c_trait_implementations) =
-                                    trait_implementations.get(&geometric_product_2_result.multi_vector_class().class_name)
-                                {
-                                    if let Some(c_pair_trait_implementations) =
-                                        c_trait_implementations.2.get(&parameter_b.multi_vector_class().class_name)
-                                    {
-                                        let transformation = MultiVectorClass::derive_sandwich_product(
-                                            "Transformation",
-                                            geometric_product,
-                                            geometric_product_2,
-                                            reversal,
-                                            c_pair_trait_implementations.1.get("Into"),
-                                            parameter_a,
-                                            parameter_b,
-                                        );
-                                        emitter.emit(&transformation).unwrap();
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
+        if options.tables && algebra_descriptor.generator_squares.len() > MAX_CAYLEY_TABLE_GENERATORS {
+            if !options.quiet {
+                println!(
+                    "{}: tables omitted for {} generators",
+                    algebra_descriptor.name,
+                    algebra_descriptor.generator_squares.len()
+                );
             }
+        } else if options.tables {
+            let tables = AlgebraTables::new(algebra_descriptor);
+            tables.write_json(&mut std::fs::File::create(path.with_extension("tables.json"))?)?;
+            tables.write_csv(&mut std::fs::File::create(path.with_extension("tables.csv"))?)?;
+            tables.write_latex(&mut std::fs::File::create(path.with_extension("tables.tex"))?)?;
         }
+        Ok(())
+    });
+    if let Err(error) = result {
+        eprintln!("{}: {}", path.display(), error);
+        std::process::exit(1);
     }
 }
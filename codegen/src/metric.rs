@@ -0,0 +1,270 @@
+//! Products of basis elements for metrics which are not diagonal
+//!
+//! The basis elements are outer products of the generators, so their geometric product is in general a sum of basis elements.
+//! It is computed by changing to a basis in which the metric is diagonal, multiplying there and changing back.
+
+use crate::algebra::{BasisElement, BasisElementIndex, GeometricAlgebra};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct Rational {
+    numerator: i64,
+    denominator: i64,
+}
+
+fn greatest_common_divisor(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a.abs()
+    } else {
+        greatest_common_divisor(b, a % b)
+    }
+}
+
+impl Rational {
+    fn new(numerator: i64, denominator: i64) -> Self {
+        let divisor = greatest_common_divisor(numerator, denominator) * denominator.signum();
+        Self {
+            numerator: numerator / divisor,
+            denominator: denominator / divisor,
+        }
+    }
+
+    fn integer(value: i64) -> Self {
+        Self::new(value, 1)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.numerator == 0
+    }
+}
+
+impl std::ops::Add for Rational {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(
+            self.numerator * other.denominator + other.numerator * self.denominator,
+            self.denominator * other.denominator,
+        )
+    }
+}
+
+impl std::ops::Mul for Rational {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self::new(self.numerator * other.numerator, self.denominator * other.denominator)
+    }
+}
+
+impl std::ops::Div for Rational {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        Self::new(self.numerator * other.denominator, self.denominator * other.numerator)
+    }
+}
+
+impl std::ops::Neg for Rational {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.numerator, self.denominator)
+    }
+}
+
+/// Coefficients of basis elements, indexed like `BasisElement::index`
+type MultiVector = std::collections::BTreeMap<BasisElementIndex, Rational>;
+
+fn accumulate(multi_vector: &mut MultiVector, index: BasisElementIndex, coefficient: Rational) {
+    let sum = *multi_vector.get(&index).unwrap_or(&Rational::integer(0)) + coefficient;
+    if sum.is_zero() {
+        multi_vector.remove(&index);
+    } else {
+        multi_vector.insert(index, sum);
+    }
+}
+
+/// Sign of reordering the generators of `a * b` into the ascending order of their combined index
+fn reordering_sign(a: BasisElementIndex, b: BasisElementIndex, unit_algebra: &GeometricAlgebra) -> i64 {
+    BasisElement::product(&BasisElement::from_index(a), &BasisElement::from_index(b), unit_algebra).scalar as i64
+}
+
+pub struct ChangeOfBasis {
+    /// Squares of the orthogonal generators
+    squares: Vec<Rational>,
+    /// The generators of the algebra expressed in the orthogonal generators
+    to_orthogonal: Vec<MultiVector>,
+    /// The orthogonal generators expressed in the generators of the algebra
+    from_orthogonal: Vec<MultiVector>,
+    unit_squares: Vec<isize>,
+}
+
+impl ChangeOfBasis {
+    /// Finds an orthogonal basis by symmetric Gaussian elimination of the metric
+    pub fn new(algebra: &GeometricAlgebra) -> Self {
+        let metric = algebra.metric();
+        let n = metric.len();
+        let mut congruent = metric
+            .iter()
+            .map(|row| row.iter().map(|value| Rational::integer(*value as i64)).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        // Columns are the orthogonal generators in terms of the original ones, rows of the inverse the other way around
+        let mut transformation = (0..n)
+            .map(|i| (0..n).map(|j| Rational::integer((i == j) as i64)).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let mut inverse = transformation.clone();
+        // Replaces generator `target` by `target + factor * source`
+        let mut add_generator = |congruent: &mut Vec<Vec<Rational>>, target: usize, source: usize, factor: Rational| {
+            for row in 0..n {
+                transformation[row][target] = transformation[row][target] + factor * transformation[row][source];
+                congruent[row][target] = congruent[row][target] + factor * congruent[row][source];
+            }
+            for column in 0..n {
+                congruent[target][column] = congruent[target][column] + factor * congruent[source][column];
+                inverse[source][column] = inverse[source][column] + -factor * inverse[target][column];
+            }
+        };
+        for i in 0..n {
+            if congruent[i][i].is_zero() {
+                if let Some(j) = (i + 1..n).find(|j| !congruent[i][*j].is_zero()) {
+                    let two = Rational::integer(2);
+                    let factor = if (congruent[j][j] + two * congruent[i][j]).is_zero() {
+                        Rational::integer(-1)
+                    } else {
+                        Rational::integer(1)
+                    };
+                    add_generator(&mut congruent, i, j, factor);
+                }
+            }
+            if congruent[i][i].is_zero() {
+                continue;
+            }
+            for j in i + 1..n {
+                let factor = -(congruent[i][j] / congruent[i][i]);
+                add_generator(&mut congruent, j, i, factor);
+            }
+        }
+        let columns = |matrix: &Vec<Vec<Rational>>| {
+            (0..n)
+                .map(|k| {
+                    let mut vector = MultiVector::new();
+                    for (i, row) in matrix.iter().enumerate() {
+                        accumulate(&mut vector, 1 << i, row[k]);
+                    }
+                    vector
+                })
+                .collect::<Vec<_>>()
+        };
+        Self {
+            squares: (0..n).map(|i| congruent[i][i]).collect(),
+            to_orthogonal: columns(&inverse),
+            from_orthogonal: columns(&transformation),
+            unit_squares: vec![1; n],
+        }
+    }
+
+    fn unit_algebra(&self) -> GeometricAlgebra<'_> {
+        GeometricAlgebra {
+            generator_squares: &self.unit_squares,
+            generator_names: &[],
+            generator_inner_products: &[],
+        }
+    }
+
+    /// Maps a basis element to the outer product of the images of its generators
+    fn outermorphism(&self, index: BasisElementIndex, images: &[MultiVector]) -> MultiVector {
+        let unit_algebra = self.unit_algebra();
+        let mut result = MultiVector::new();
+        result.insert(0, Rational::integer(1));
+        for generator in BasisElement::from_index(index).component_bits() {
+            let mut outer_product = MultiVector::new();
+            for (a_index, a_coefficient) in result.iter() {
+                for (b_index, b_coefficient) in images[generator].iter() {
+                    if a_index & b_index == 0 {
+                        let sign = Rational::integer(reordering_sign(*a_index, *b_index, &unit_algebra));
+                        accumulate(&mut outer_product, a_index | b_index, sign * *a_coefficient * *b_coefficient);
+                    }
+                }
+            }
+            result = outer_product;
+        }
+        result
+    }
+
+    /// Geometric product of two basis elements as a sum of basis elements
+    pub fn product(&self, a: &BasisElement, b: &BasisElement) -> Vec<BasisElement> {
+        let unit_algebra = self.unit_algebra();
+        let a_orthogonal = self.outermorphism(a.index, &self.to_orthogonal);
+        let b_orthogonal = self.outermorphism(b.index, &self.to_orthogonal);
+        let mut product_orthogonal = MultiVector::new();
+        for (a_index, a_coefficient) in a_orthogonal.iter() {
+            for (b_index, b_coefficient) in b_orthogonal.iter() {
+                let squares = BasisElement::from_index(a_index & b_index)
+                    .component_bits()
+                    .fold(Rational::integer(reordering_sign(*a_index, *b_index, &unit_algebra)), |product, i| {
+                        product * self.squares[i]
+                    });
+                accumulate(&mut product_orthogonal, a_index ^ b_index, squares * *a_coefficient * *b_coefficient);
+            }
+        }
+        let mut product = MultiVector::new();
+        for (index, coefficient) in product_orthogonal.iter() {
+            for (result_index, result_coefficient) in self.outermorphism(*index, &self.from_orthogonal) {
+                accumulate(&mut product, result_index, *coefficient * result_coefficient);
+            }
+        }
+        product
+            .into_iter()
+            .map(|(index, coefficient)| {
+                assert_eq!(coefficient.denominator, 1, "non-integer coefficient in product of basis elements");
+                BasisElement {
+                    scalar: (coefficient.numerator * (a.scalar * b.scalar) as i64) as isize,
+                    index,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn products(
+        generator_squares: &[isize],
+        generator_inner_products: &[(usize, usize, isize)],
+        a: BasisElementIndex,
+        b: BasisElementIndex,
+    ) -> Vec<BasisElement> {
+        let algebra = GeometricAlgebra {
+            generator_squares,
+            generator_names: &[],
+            generator_inner_products,
+        };
+        ChangeOfBasis::new(&algebra).product(&BasisElement::from_index(a), &BasisElement::from_index(b))
+    }
+
+    #[test]
+    fn square_of_bivector() {
+        // (a ∧ b)² = (a · b)² - a² b²
+        assert_eq!(products(&[2, 3], &[(0, 1, 1)], 0b11, 0b11), vec![BasisElement { scalar: -5, index: 0 }]);
+    }
+
+    #[test]
+    fn null_basis() {
+        let (no, ni) = (0b01, 0b10);
+        assert_eq!(products(&[0, 0], &[(0, 1, -1)], no, no), vec![]);
+        assert_eq!(
+            products(&[0, 0], &[(0, 1, -1)], no, ni),
+            vec![BasisElement { scalar: -1, index: 0 }, BasisElement { scalar: 1, index: no | ni }]
+        );
+        assert_eq!(
+            products(&[0, 0], &[(0, 1, -1)], ni, no),
+            vec![BasisElement { scalar: -1, index: 0 }, BasisElement { scalar: -1, index: no | ni }]
+        );
+        assert_eq!(
+            products(&[0, 0], &[(0, 1, -1)], no | ni, no | ni),
+            vec![BasisElement { scalar: 1, index: 0 }]
+        );
+    }
+}
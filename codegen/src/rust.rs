@@ -1,11 +1,13 @@
 use crate::{
-    ast::{AstNode, DataType, Expression, ExpressionContent, Parameter},
-    emit::{camel_to_snake_case, emit_element_name, emit_indentation},
+    algebra::{BasisElement, MultiVectorClass},
+    ast::{grade_mask, AstNode, DataType, Expression, ExpressionContent, Parameter, ScalarFunction},
+    emit::{camel_to_snake_case, element_accessor_name, element_label, emit_element_name, emit_indentation},
 };
 
 fn emit_data_type<W: std::io::Write>(collector: &mut W, data_type: &DataType) -> std::io::Result<()> {
     match data_type {
         DataType::Integer => collector.write_all(b"isize"),
+        DataType::GradeMask => collector.write_all(b"u128"),
         DataType::SimdVector(size) if *size == 1 => collector.write_all(b"f32"),
         DataType::SimdVector(size) => collector.write_fmt(format_args!("Simd32x{}", *size)),
         DataType::MultiVector(class) if class.is_scalar() => collector.write_all(b"f32"),
@@ -13,6 +15,47 @@ fn emit_data_type<W: std::io::Write>(collector: &mut W, data_type: &DataType) ->
     }
 }
 
+fn emit_returned_expression<W: std::io::Write>(collector: &mut W, expression: &Expression) -> std::io::Result<()> {
+    if let ExpressionContent::Conversion(_source_class, _destination_class, inner_expression) = &expression.content {
+        emit_expression(collector, inner_expression)?;
+        collector.write_all(b".into()")
+    } else {
+        emit_expression(collector, expression)
+    }
+}
+
+/// Emits the receiver of a method call, which has to be parenthesized unless it is a primary expression
+fn emit_receiver<W: std::io::Write>(collector: &mut W, expression: &Expression) -> std::io::Result<()> {
+    let parenthesize = matches!(
+        expression.content,
+        ExpressionContent::Constant(_, _)
+            | ExpressionContent::Select(_, _, _)
+            | ExpressionContent::Add(_, _)
+            | ExpressionContent::Subtract(_, _)
+            | ExpressionContent::Multiply(_, _)
+            | ExpressionContent::Divide(_, _)
+    );
+    if parenthesize {
+        collector.write_all(b"(")?;
+    }
+    emit_expression(collector, expression)?;
+    if parenthesize {
+        collector.write_all(b")")?;
+    }
+    Ok(())
+}
+
+/// Emits an operand of `operator`, parenthesized if it binds weaker than `operator`
+fn emit_operand<W: std::io::Write>(collector: &mut W, operator: &ExpressionContent, operand: &Expression, is_rhs: bool) -> std::io::Result<()> {
+    if operand.needs_parentheses(operator, is_rhs) {
+        collector.write_all(b"(")?;
+        emit_expression(collector, operand)?;
+        collector.write_all(b")")
+    } else {
+        emit_expression(collector, operand)
+    }
+}
+
 fn emit_expression<W: std::io::Write>(collector: &mut W, expression: &Expression) -> std::io::Result<()> {
     match &expression.content {
         ExpressionContent::None => unreachable!(),
@@ -20,7 +63,7 @@ fn emit_expression<W: std::io::Write>(collector: &mut W, expression: &Expression
             collector.write_all(name.bytes().collect::<Vec<_>>().as_slice())?;
         }
         ExpressionContent::InvokeInstanceMethod(_result_class, inner_expression, method_name, _, arguments) => {
-            emit_expression(collector, inner_expression)?;
+            emit_receiver(collector, inner_expression)?;
             collector.write_all(b".")?;
             camel_to_snake_case(collector, method_name)?;
             collector.write_all(b"(")?;
@@ -59,18 +102,28 @@ fn emit_expression<W: std::io::Write>(collector: &mut W, expression: &Expression
             }
             collector.write_all(b")")?;
         }
-        ExpressionContent::Conversion(_source_class, _destination_class, inner_expression) => {
+        // Spelled out, because the destination type can only be inferred from a returned expression, see `emit_returned_expression`
+        ExpressionContent::Conversion(_source_class, destination_class, inner_expression) => {
+            collector.write_all(b"Into::<")?;
+            emit_data_type(collector, &DataType::MultiVector(destination_class))?;
+            collector.write_all(b">::into(")?;
             emit_expression(collector, inner_expression)?;
-            collector.write_all(b".into()")?;
+            collector.write_all(b")")?;
         }
         ExpressionContent::Select(condition_expression, then_expression, else_expression) => {
             collector.write_all(b"if ")?;
             emit_expression(collector, condition_expression)?;
             collector.write_all(b" { ")?;
             emit_expression(collector, then_expression)?;
-            collector.write_all(b" } else { ")?;
-            emit_expression(collector, else_expression)?;
-            collector.write_all(b" }")?;
+            // Nested selections are chained, because clippy rejects an `if` which is the only expression of an `else` block
+            if let ExpressionContent::Select(_, _, _) = else_expression.content {
+                collector.write_all(b" } else ")?;
+                emit_expression(collector, else_expression)?;
+            } else {
+                collector.write_all(b" } else { ")?;
+                emit_expression(collector, else_expression)?;
+                collector.write_all(b" }")?;
+            }
         }
         ExpressionContent::Access(inner_expression, array_index) => {
             emit_expression(collector, inner_expression)?;
@@ -130,6 +183,7 @@ fn emit_expression<W: std::io::Write>(collector: &mut W, expression: &Expression
         }
         ExpressionContent::Constant(data_type, values) => match data_type {
             DataType::Integer => collector.write_fmt(format_args!("{}", values[0] as f32))?,
+            DataType::GradeMask => collector.write_fmt(format_args!("{}", grade_mask(values)))?,
             DataType::SimdVector(_size) => {
                 if expression.size == 1 {
                     collector.write_fmt(format_args!("{:.1}", values[0] as f32))?;
@@ -154,9 +208,33 @@ fn emit_expression<W: std::io::Write>(collector: &mut W, expression: &Expression
             _ => unreachable!(),
         },
         ExpressionContent::SquareRoot(inner_expression) => {
-            emit_expression(collector, inner_expression)?;
+            emit_receiver(collector, inner_expression)?;
             collector.write_all(b".sqrt()")?;
         }
+        ExpressionContent::Function(function, inner_expression) => {
+            emit_receiver(collector, inner_expression)?;
+            collector.write_all(match function {
+                ScalarFunction::Cosine => b".cos()",
+                ScalarFunction::Sine => b".sin()",
+                ScalarFunction::HyperbolicCosine => b".cosh()",
+                ScalarFunction::HyperbolicSine => b".sinh()",
+                ScalarFunction::HyperbolicArcTangent => b".atanh()",
+                ScalarFunction::Exponential => b".exp()",
+                ScalarFunction::Logarithm => b".ln()",
+            })?;
+        }
+        ExpressionContent::ArcTangent2(lhs, rhs) => {
+            emit_receiver(collector, lhs)?;
+            collector.write_all(b".atan2(")?;
+            emit_expression(collector, rhs)?;
+            collector.write_all(b")")?;
+        }
+        ExpressionContent::Multiply(lhs, rhs)
+            if expression.size == 1 && matches!(&rhs.content, ExpressionContent::Constant(_, values) if values[..] == [-1]) =>
+        {
+            collector.write_all(b"-")?;
+            emit_expression(collector, lhs)?;
+        }
         ExpressionContent::Add(lhs, rhs)
         | ExpressionContent::Subtract(lhs, rhs)
         | ExpressionContent::Multiply(lhs, rhs)
@@ -165,7 +243,7 @@ fn emit_expression<W: std::io::Write>(collector: &mut W, expression: &Expression
         | ExpressionContent::Equal(lhs, rhs)
         | ExpressionContent::LogicAnd(lhs, rhs)
         | ExpressionContent::BitShiftRight(lhs, rhs) => {
-            emit_expression(collector, lhs)?;
+            emit_operand(collector, &expression.content, lhs, false)?;
             collector.write_all(match expression.content {
                 ExpressionContent::Add(_, _) => b" + ",
                 ExpressionContent::Subtract(_, _) => b" - ",
@@ -177,7 +255,7 @@ fn emit_expression<W: std::io::Write>(collector: &mut W, expression: &Expression
                 ExpressionContent::BitShiftRight(_, _) => b" >> ",
                 _ => unreachable!(),
             })?;
-            emit_expression(collector, rhs)?;
+            emit_operand(collector, &expression.content, rhs, true)?;
         }
     }
     Ok(())
@@ -200,15 +278,84 @@ fn emit_assign_trait<W: std::io::Write>(collector: &mut W, result: &Parameter, p
     collector.write_all(b"(other);\n    }\n}\n\n")
 }
 
+fn emit_parameter_name<W: std::io::Write>(
+    collector: &mut W,
+    class: &MultiVectorClass,
+    element: &BasisElement,
+    generator_names: &[String],
+) -> std::io::Result<()> {
+    match class.element_aliases.get(&element.index) {
+        Some(alias) => collector.write_all(alias.as_bytes()),
+        None => emit_element_name(collector, element, generator_names),
+    }
+}
+
+/// Getter and setter of a single element, `sign` converts between the stored value and the accessed value
+fn emit_accessors<W: std::io::Write>(collector: &mut W, name: &str, sign: &str, element_index: usize, indentation: usize) -> std::io::Result<()> {
+    emit_indentation(collector, indentation)?;
+    collector.write_all(b"#[inline(always)]\n")?;
+    emit_indentation(collector, indentation)?;
+    collector.write_fmt(format_args!("pub fn {}(&self) -> f32 {{\n", name))?;
+    emit_indentation(collector, indentation + 1)?;
+    collector.write_fmt(format_args!("{}self[{}]\n", sign, element_index))?;
+    emit_indentation(collector, indentation)?;
+    collector.write_all(b"}\n")?;
+    emit_indentation(collector, indentation)?;
+    collector.write_all(b"#[inline(always)]\n")?;
+    emit_indentation(collector, indentation)?;
+    collector.write_fmt(format_args!("pub fn set_{}(&mut self, {}: f32) {{\n", name, name))?;
+    emit_indentation(collector, indentation + 1)?;
+    collector.write_fmt(format_args!("self[{}] = {}{};\n", element_index, sign, name))?;
+    emit_indentation(collector, indentation)?;
+    collector.write_all(b"}\n")
+}
+
+fn emit_variable_assignment<W: std::io::Write>(
+    collector: &mut W,
+    name: &str,
+    data_type: Option<&DataType>,
+    expression: &Expression,
+    is_mutable: bool,
+) -> std::io::Result<()> {
+    if let Some(data_type) = data_type {
+        collector.write_fmt(format_args!("let {}{}", if is_mutable { "mut " } else { "" }, name))?;
+        collector.write_all(b": ")?;
+        emit_data_type(collector, data_type)?;
+    } else {
+        collector.write_fmt(format_args!("{}", name))?;
+    }
+    collector.write_all(b" = ")?;
+    emit_expression(collector, expression)?;
+    collector.write_all(b";\n")
+}
+
+/// If any of the statements assigns to the variable `name` after its declaration
+fn is_reassigned(statements: &[AstNode], name: &str) -> bool {
+    statements.iter().any(|statement| match statement {
+        AstNode::VariableAssignment {
+            name: assigned_name,
+            data_type: None,
+            ..
+        } => *assigned_name == name,
+        AstNode::IfThenBlock { body, .. } | AstNode::WhileLoopBlock { body, .. } => is_reassigned(body, name),
+        _ => false,
+    })
+}
+
 pub fn emit_code<W: std::io::Write>(collector: &mut W, ast_node: &AstNode, indentation: usize) -> std::io::Result<()> {
     match &ast_node {
         AstNode::None => {}
-        AstNode::Preamble => {
-            collector.write_all(b"#![allow(clippy::assign_op_pattern)]\n")?;
+        AstNode::Preamble { runtime_crate } => {
+            // Inner attributes are not permitted in files which are included by other crates via `include!`
+            if *runtime_crate == "crate" {
+                collector.write_all(b"#![allow(clippy::assign_op_pattern)]\n#![allow(clippy::from_over_into)]\n")?;
+            }
+            collector.write_fmt(format_args!("use {}::{{simd::*, *}};\n", runtime_crate))?;
+            // Trait selections in the descriptor can leave some of the operators unimplemented
             collector
-                .write_all(b"use crate::{simd::*, *};\nuse std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};\n\n")?;
+                .write_all(b"#[allow(unused_imports)]\nuse std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};\n\n")?;
         }
-        AstNode::ClassDefinition { class } => {
+        AstNode::ClassDefinition { class, generator_names } => {
             if class.is_scalar() {
                 return Ok(());
             }
@@ -223,7 +370,7 @@ pub fn emit_code<W: std::io::Write>(collector: &mut W, ast_node: &AstNode, inden
                     if i > 0 {
                         collector.write_all(b", ")?;
                     }
-                    collector.write_fmt(format_args!("{}", element))?;
+                    collector.write_all(element_label(element, generator_names).as_bytes())?;
                 }
                 collector.write_all(b"\n")?;
                 emit_indentation(collector, indentation + 1)?;
@@ -244,7 +391,7 @@ pub fn emit_code<W: std::io::Write>(collector: &mut W, ast_node: &AstNode, inden
                     if j > 0 || i > 0 {
                         collector.write_all(b", ")?;
                     }
-                    collector.write_fmt(format_args!("{}", element))?;
+                    collector.write_all(element_label(element, generator_names).as_bytes())?;
                 }
                 for _ in group.len()..simd_widths[j] {
                     collector.write_all(b", 0")?;
@@ -252,7 +399,7 @@ pub fn emit_code<W: std::io::Write>(collector: &mut W, ast_node: &AstNode, inden
             }
             collector.write_all(b"\n")?;
             emit_indentation(collector, indentation + 1)?;
-            collector.write_fmt(format_args!("elements: [f32; {}],\n", simd_widths.iter().fold(0, |a, b| a + b)))?;
+            collector.write_fmt(format_args!("elements: [f32; {}],\n", simd_widths.iter().sum::<usize>()))?;
             emit_indentation(collector, indentation)?;
             collector.write_all(b"}\n\n")?;
             emit_indentation(collector, indentation)?;
@@ -267,7 +414,7 @@ pub fn emit_code<W: std::io::Write>(collector: &mut W, ast_node: &AstNode, inden
                     if element_index > 0 {
                         collector.write_all(b", ")?;
                     }
-                    emit_element_name(collector, element)?;
+                    emit_parameter_name(collector, class, element, generator_names)?;
                     collector.write_all(b": f32")?;
                     element_index += 1;
                 }
@@ -281,7 +428,7 @@ pub fn emit_code<W: std::io::Write>(collector: &mut W, ast_node: &AstNode, inden
                     if element_index > 0 {
                         collector.write_all(b", ")?;
                     }
-                    emit_element_name(collector, element)?;
+                    emit_parameter_name(collector, class, element, generator_names)?;
                     element_index += 1;
                 }
                 for _ in group.len()..simd_widths[j] {
@@ -291,6 +438,10 @@ pub fn emit_code<W: std::io::Write>(collector: &mut W, ast_node: &AstNode, inden
             collector.write_all(b"] }\n")?;
             emit_indentation(collector, indentation + 1)?;
             collector.write_all(b"}\n")?;
+            if class.grouped_basis.len() > 7 {
+                emit_indentation(collector, indentation + 1)?;
+                collector.write_all(b"#[allow(clippy::too_many_arguments)]\n")?;
+            }
             emit_indentation(collector, indentation + 1)?;
             collector.write_all(b"pub const fn from_groups(")?;
             for (j, group) in class.grouped_basis.iter().enumerate() {
@@ -334,6 +485,23 @@ pub fn emit_code<W: std::io::Write>(collector: &mut W, ast_node: &AstNode, inden
                 emit_indentation(collector, indentation + 1)?;
                 collector.write_all(b"}\n")?;
             }
+            let mut element_index = 0;
+            for group in class.grouped_basis.iter() {
+                for element in group.iter() {
+                    let sign = if element.scalar < 0 { "-" } else { "" };
+                    emit_accessors(
+                        collector,
+                        &element_accessor_name(element, generator_names),
+                        sign,
+                        element_index,
+                        indentation + 1,
+                    )?;
+                    if let Some(alias) = class.element_aliases.get(&element.index) {
+                        emit_accessors(collector, alias, "", element_index, indentation + 1)?;
+                    }
+                    element_index += 1;
+                }
+            }
             emit_indentation(collector, indentation)?;
             collector.write_all(b"}\n\n")?;
             emit_indentation(collector, indentation)?;
@@ -443,7 +611,11 @@ pub fn emit_code<W: std::io::Write>(collector: &mut W, ast_node: &AstNode, inden
             for group in class.grouped_basis.iter() {
                 for element in group.iter() {
                     emit_indentation(collector, indentation + 3)?;
-                    collector.write_fmt(format_args!(".field(\"{}\", &self[{}])\n", element, element_index))?;
+                    collector.write_fmt(format_args!(
+                        ".field(\"{}\", &self[{}])\n",
+                        element_label(element, generator_names),
+                        element_index
+                    ))?;
                     element_index += 1;
                 }
             }
@@ -454,22 +626,28 @@ pub fn emit_code<W: std::io::Write>(collector: &mut W, ast_node: &AstNode, inden
             emit_indentation(collector, indentation)?;
             collector.write_all(b"}\n\n")?;
         }
+        AstNode::TraitDefinition { name, documentation, binary } => {
+            collector.write_fmt(format_args!(
+                "/// {}\npub trait {}{} {{\n",
+                documentation,
+                name,
+                if *binary { "<T>" } else { "" }
+            ))?;
+            emit_indentation(collector, indentation + 1)?;
+            collector.write_all(b"type Output;\n")?;
+            emit_indentation(collector, indentation + 1)?;
+            collector.write_all(b"fn ")?;
+            camel_to_snake_case(collector, name)?;
+            collector.write_all(if *binary { b"(self, other: T)" } else { b"(self)" })?;
+            collector.write_all(b" -> Self::Output;\n}\n\n")?;
+        }
         AstNode::ReturnStatement { expression } => {
             collector.write_all(b"return ")?;
-            emit_expression(collector, expression)?;
+            emit_returned_expression(collector, expression)?;
             collector.write_all(b";\n")?;
         }
         AstNode::VariableAssignment { name, data_type, expression } => {
-            if let Some(data_type) = data_type {
-                collector.write_fmt(format_args!("let mut {}", name))?;
-                collector.write_all(b": ")?;
-                emit_data_type(collector, data_type)?;
-            } else {
-                collector.write_fmt(format_args!("{}", name))?;
-            }
-            collector.write_all(b" = ")?;
-            emit_expression(collector, expression)?;
-            collector.write_all(b";\n")?;
+            emit_variable_assignment(collector, name, data_type.as_ref(), expression, true)?;
         }
         AstNode::IfThenBlock { condition, body } | AstNode::WhileLoopBlock { condition, body } => {
             collector.write_all(match &ast_node {
@@ -487,7 +665,8 @@ pub fn emit_code<W: std::io::Write>(collector: &mut W, ast_node: &AstNode, inden
             collector.write_all(b"}\n")?;
         }
         AstNode::TraitImplementation { result, parameters, body } => {
-            if result.data_type.is_scalar()
+            // Implementations for f32 alone are hand written, as every algebra would implement them otherwise
+            if (result.data_type.is_scalar() || !parameters.is_empty())
                 && !parameters
                     .iter()
                     .any(|parameter| matches!(parameter.data_type, DataType::MultiVector(class) if !class.is_scalar()))
@@ -544,11 +723,20 @@ pub fn emit_code<W: std::io::Write>(collector: &mut W, ast_node: &AstNode, inden
                 emit_indentation(collector, indentation + 2)?;
                 if i + 1 == body.len() {
                     if let AstNode::ReturnStatement { expression } = statement {
-                        emit_expression(collector, expression)?;
+                        emit_returned_expression(collector, expression)?;
                         collector.write_all(b"\n")?;
                         break;
                     }
                 }
+                if let AstNode::VariableAssignment {
+                    name,
+                    data_type: Some(data_type),
+                    expression,
+                } = statement
+                {
+                    emit_variable_assignment(collector, name, Some(data_type), expression, is_reassigned(body, name))?;
+                    continue;
+                }
                 emit_code(collector, statement, indentation + 2)?;
             }
             emit_indentation(collector, indentation + 1)?;
@@ -0,0 +1,268 @@
+use crate::{algebra::BasisElement, dsl::AlgebraDescriptor, emit::element_label};
+
+/// Sum of basis elements, e.g. `-1+no_ni`, with `name` rendering a basis element without its sign
+fn sum_label<F>(elements: &[BasisElement], name: F) -> String
+where
+    F: Fn(&BasisElement) -> String,
+{
+    if elements.is_empty() {
+        return "0".to_string();
+    }
+    let mut label = String::new();
+    for (i, element) in elements.iter().enumerate() {
+        if element.scalar < 0 {
+            label.push('-');
+        } else if i > 0 {
+            label.push('+');
+        }
+        if element.index == 0 {
+            label.push_str(&element.scalar.abs().to_string());
+        } else {
+            if element.scalar.abs() != 1 {
+                label.push_str(&element.scalar.abs().to_string());
+            }
+            label.push_str(&name(element));
+        }
+    }
+    label
+}
+
+/// Sum of basis elements, e.g. `-1+no_ni`
+pub fn product_label(elements: &[BasisElement], generator_names: &[String]) -> String {
+    sum_label(elements, |element| element.name(generator_names))
+}
+
+fn latex_escape(text: &str) -> String {
+    text.replace('_', "\\_")
+}
+
+/// Name of a basis element in math mode, e.g. `e_{012}` or `\mathit{no\_ni}`
+fn latex_name(element: &BasisElement, generator_names: &[String]) -> String {
+    if generator_names.is_empty() {
+        format!("e_{{{}}}", &element.name(generator_names)[1..])
+    } else {
+        format!("\\mathit{{{}}}", latex_escape(&element.name(generator_names)))
+    }
+}
+
+/// Multiplication tables of all products and the images of all involutions of an algebra
+///
+/// Rows and columns follow `GeometricAlgebra::sorted_basis`, including the orientation of its basis elements.
+/// The rows of a product table are the left factors and its columns the right factors.
+#[derive(Clone, Debug)]
+pub struct AlgebraTables {
+    pub algebra_name: String,
+    pub generator_names: Vec<String>,
+    pub basis: Vec<BasisElement>,
+    /// For each product the sum of basis elements at each row and column
+    pub products: Vec<(&'static str, Vec<Vec<Vec<BasisElement>>>)>,
+    /// For each involution the image of each basis element, which is empty for zero
+    pub involutions: Vec<(&'static str, Vec<Vec<BasisElement>>)>,
+}
+
+impl AlgebraTables {
+    pub fn new(algebra_descriptor: &AlgebraDescriptor) -> Self {
+        let algebra = algebra_descriptor.algebra();
+        let basis = algebra.sorted_basis();
+        let position = |element: &BasisElement| basis.iter().position(|basis_element| basis_element.index == element.index).unwrap();
+        let sorted = |mut elements: Vec<BasisElement>| {
+            elements.retain(|element| element.scalar != 0);
+            elements.sort_by_key(|element| position(element));
+            elements
+        };
+        let products = algebra_descriptor
+            .products(&basis, &basis)
+            .into_iter()
+            .map(|(name, product)| {
+                let mut table = vec![vec![Vec::<BasisElement>::new(); basis.len()]; basis.len()];
+                for term in product.terms.iter() {
+                    let (row, column) = (position(&term.factor_a), position(&term.factor_b));
+                    // Rescales terms whose factors are oriented differently than the basis
+                    let orientation = term.factor_a.scalar * basis[row].scalar * term.factor_b.scalar * basis[column].scalar;
+                    let sum = &mut table[row][column];
+                    if let Some(element) = sum.iter_mut().find(|element| element.index == term.product.index) {
+                        element.scalar += orientation * term.product.scalar;
+                    } else {
+                        sum.push(BasisElement {
+                            scalar: orientation * term.product.scalar,
+                            index: term.product.index,
+                        });
+                    }
+                }
+                let table = table.into_iter().map(|row| row.into_iter().map(sorted).collect()).collect();
+                (name, table)
+            })
+            .collect();
+        let involutions = algebra_descriptor
+            .involutions(&basis)
+            .into_iter()
+            .map(|(name, involution)| {
+                let images = basis
+                    .iter()
+                    .map(|element| {
+                        sorted(
+                            involution
+                                .terms
+                                .iter()
+                                .filter(|(key, _value)| key.index == element.index)
+                                .map(|(_key, value)| value.clone())
+                                .collect(),
+                        )
+                    })
+                    .collect();
+                (name, images)
+            })
+            .collect();
+        Self {
+            algebra_name: algebra_descriptor.name.clone(),
+            generator_names: algebra_descriptor.generator_names.clone(),
+            basis,
+            products,
+            involutions,
+        }
+    }
+
+    fn basis_labels(&self) -> Vec<String> {
+        self.basis.iter().map(|element| element_label(element, &self.generator_names)).collect()
+    }
+
+    fn label(&self, elements: &[BasisElement]) -> String {
+        product_label(elements, &self.generator_names)
+    }
+
+    fn latex_label(&self, elements: &[BasisElement]) -> String {
+        format!("${}$", sum_label(elements, |element| latex_name(element, &self.generator_names)))
+    }
+
+    pub fn write_json<W: std::io::Write>(&self, collector: &mut W) -> std::io::Result<()> {
+        let quoted_list = |labels: &[String]| labels.iter().map(|label| format!("{:?}", label)).collect::<Vec<_>>().join(", ");
+        collector.write_fmt(format_args!(
+            "{{\n    \"algebra\": {:?},\n    \"basis\": [{}],\n    \"products\": {{\n",
+            self.algebra_name,
+            quoted_list(&self.basis_labels())
+        ))?;
+        for (i, (name, table)) in self.products.iter().enumerate() {
+            collector.write_fmt(format_args!("        {:?}: [\n", name))?;
+            for (j, row) in table.iter().enumerate() {
+                let labels = row.iter().map(|sum| self.label(sum)).collect::<Vec<_>>();
+                collector.write_fmt(format_args!(
+                    "            [{}]{}\n",
+                    quoted_list(&labels),
+                    if j + 1 < table.len() { "," } else { "" }
+                ))?;
+            }
+            collector.write_fmt(format_args!("        ]{}\n", if i + 1 < self.products.len() { "," } else { "" }))?;
+        }
+        collector.write_all(b"    },\n    \"involutions\": {\n")?;
+        for (i, (name, images)) in self.involutions.iter().enumerate() {
+            let labels = images.iter().map(|image| self.label(image)).collect::<Vec<_>>();
+            collector.write_fmt(format_args!(
+                "        {:?}: [{}]{}\n",
+                name,
+                quoted_list(&labels),
+                if i + 1 < self.involutions.len() { "," } else { "" }
+            ))?;
+        }
+        collector.write_all(b"    }\n}\n")
+    }
+
+    /// Writes one table per product, followed by a table with one column per involution, separated by empty lines
+    ///
+    /// The top left cell of each table is its name. Labels never contain commas or quotes, so no cell is quoted.
+    pub fn write_csv<W: std::io::Write>(&self, collector: &mut W) -> std::io::Result<()> {
+        let basis_labels = self.basis_labels();
+        for (name, table) in self.products.iter() {
+            collector.write_fmt(format_args!("{},{}\n", name, basis_labels.join(",")))?;
+            for (basis_label, row) in basis_labels.iter().zip(table.iter()) {
+                let labels = row.iter().map(|sum| self.label(sum)).collect::<Vec<_>>();
+                collector.write_fmt(format_args!("{},{}\n", basis_label, labels.join(",")))?;
+            }
+            collector.write_all(b"\n")?;
+        }
+        let names = self.involutions.iter().map(|(name, _images)| *name).collect::<Vec<_>>();
+        collector.write_fmt(format_args!("Involutions,{}\n", names.join(",")))?;
+        for (i, basis_label) in basis_labels.iter().enumerate() {
+            let labels = self.involutions.iter().map(|(_name, images)| self.label(&images[i])).collect::<Vec<_>>();
+            collector.write_fmt(format_args!("{},{}\n", basis_label, labels.join(",")))?;
+        }
+        Ok(())
+    }
+
+    /// Writes a `table` environment per product and one for the involutions, to be included into a LaTeX document
+    pub fn write_latex<W: std::io::Write>(&self, collector: &mut W) -> std::io::Result<()> {
+        let basis_labels = self
+            .basis
+            .iter()
+            .map(|element| self.latex_label(std::slice::from_ref(element)))
+            .collect::<Vec<_>>();
+        let mut write_table = |caption: String, column_labels: &[String], rows: Vec<Vec<String>>| -> std::io::Result<()> {
+            collector.write_fmt(format_args!(
+                "\\begin{{table}}\n\\centering\n\\caption{{{}}}\n\\begin{{tabular}}{{c|{}}}\n & {} \\\\\n\\hline\n",
+                caption,
+                "c".repeat(column_labels.len()),
+                column_labels.join(" & ")
+            ))?;
+            for (basis_label, row) in basis_labels.iter().zip(rows.iter()) {
+                collector.write_fmt(format_args!("{} & {} \\\\\n", basis_label, row.join(" & ")))?;
+            }
+            collector.write_all(b"\\end{tabular}\n\\end{table}\n\n")
+        };
+        for (name, table) in self.products.iter() {
+            let rows = table.iter().map(|row| row.iter().map(|sum| self.latex_label(sum)).collect()).collect();
+            write_table(format!("{} of {}", name, latex_escape(&self.algebra_name)), &basis_labels, rows)?;
+        }
+        let names = self.involutions.iter().map(|(name, _images)| name.to_string()).collect::<Vec<_>>();
+        let rows = (0..self.basis.len())
+            .map(|i| self.involutions.iter().map(|(_name, images)| self.latex_label(&images[i])).collect())
+            .collect();
+        write_table(format!("Involutions of {}", latex_escape(&self.algebra_name)), &names, rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn written(descriptor: &str, write: fn(&AlgebraTables, &mut Vec<u8>) -> std::io::Result<()>) -> String {
+        let tables = AlgebraTables::new(&AlgebraDescriptor::parse_inline(descriptor).unwrap());
+        let mut collector = Vec::new();
+        write(&tables, &mut collector).unwrap();
+        String::from_utf8(collector).unwrap()
+    }
+
+    #[test]
+    fn csv() {
+        let csv = written("epga1d:1,1;Scalar:1", AlgebraTables::write_csv);
+        assert!(csv.starts_with("GeometricProduct,1,e0,-e1,e01\n1,1,e0,-e1,e01\ne0,e0,1,-e01,e1\n-e1,-e1,e01,1,e0\ne01,e01,-e1,-e0,-1\n\n"));
+        assert!(csv.contains("\nRegressiveProduct,1,e0,-e1,e01\n1,0,0,0,1\n"));
+        assert!(csv.contains("\nInvolutions,"));
+    }
+
+    #[test]
+    fn json() {
+        let json = written("epga1d:1,1;Scalar:1", AlgebraTables::write_json);
+        assert!(json.starts_with("{\n    \"algebra\": \"epga1d\",\n    \"basis\": [\"1\", \"e0\", \"-e1\", \"e01\"],\n"));
+        assert!(json.contains("        \"GeometricProduct\": [\n            [\"1\", \"e0\", \"-e1\", \"e01\"],\n"));
+        assert!(json.contains("            [\"e01\", \"-e1\", \"-e0\", \"-1\"]\n        ],\n"));
+        assert!(json.ends_with("    }\n}\n"));
+    }
+
+    #[test]
+    fn latex() {
+        let latex = written("epga1d:1,1;Scalar:1", AlgebraTables::write_latex);
+        assert!(latex.starts_with("\\begin{table}\n\\centering\n\\caption{GeometricProduct of epga1d}\n\\begin{tabular}{c|cccc}\n"));
+        assert!(latex.contains("\n$e_{01}$ & $e_{01}$ & $-e_{1}$ & $-e_{0}$ & $-1$ \\\\\n"));
+        let latex = written("cga:no=0,ni=0,no.ni=-1;Scalar:1", AlgebraTables::write_latex);
+        assert!(latex.contains("\n$\\mathit{no}$ & $\\mathit{no}$ & $0$ & $1-\\mathit{no\\_ni}$ & $\\mathit{no}$ \\\\\n"));
+    }
+
+    #[test]
+    fn sums_in_null_basis() {
+        let tables = AlgebraTables::new(&AlgebraDescriptor::parse_inline("cga:no=0,ni=0,no.ni=-1;Scalar:1").unwrap());
+        let (name, table) = &tables.products[0];
+        assert_eq!(*name, "GeometricProduct");
+        assert_eq!(tables.label(&table[1][2]), "1-no_ni");
+        assert_eq!(tables.label(&table[2][1]), "1+no_ni");
+        assert_eq!(tables.label(&table[1][1]), "0");
+    }
+}
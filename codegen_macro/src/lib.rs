@@ -0,0 +1,110 @@
+//! Runs the code generator at compile time
+//!
+//! ```ignore
+//! geometric_algebra! { ppga2d: 0, 1, 1; Scalar: 1; Point: e12, e01, -e02; Motor: 1, e12, e01, -e02 }
+//! ```
+//!
+//! expands to `pub mod ppga2d { ... }`, which imports the SIMD types and traits from the `geometric_algebra` crate.
+
+use codegen::{
+    dsl::{AlgebraDescriptor, ParseError},
+    emit::Emitter,
+};
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+
+/// Joins the tokens into the single argument syntax and records the offset each token starts at
+///
+/// Only words are separated by a space (e.g. `enable CommutatorProduct`),
+/// because stringified tokens are also separated where the descriptor syntax does not expect it (e.g. `- e02`).
+fn join_tokens(input: TokenStream, config: &mut String, token_offsets: &mut Vec<(usize, Span)>) {
+    let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    for token in input {
+        match token {
+            TokenTree::Group(group) => {
+                let (open, close) = match group.delimiter() {
+                    Delimiter::Parenthesis => ("(", ")"),
+                    Delimiter::Brace => ("{", "}"),
+                    Delimiter::Bracket => ("[", "]"),
+                    Delimiter::None => ("", ""),
+                };
+                token_offsets.push((config.len(), group.span_open()));
+                config.push_str(open);
+                join_tokens(group.stream(), config, token_offsets);
+                token_offsets.push((config.len(), group.span_close()));
+                config.push_str(close);
+            }
+            TokenTree::Punct(punct) => {
+                token_offsets.push((config.len(), punct.span()));
+                config.push(punct.as_char());
+            }
+            TokenTree::Ident(_) | TokenTree::Literal(_) => {
+                let text = token.to_string();
+                if config.ends_with(is_word) && text.starts_with(is_word) {
+                    config.push(' ');
+                }
+                token_offsets.push((config.len(), token.span()));
+                config.push_str(&text);
+            }
+        }
+    }
+}
+
+/// Reports the error at the token its column points to
+fn compile_error(error: &ParseError, token_offsets: &[(usize, Span)]) -> TokenStream {
+    let span = token_offsets
+        .iter()
+        .rev()
+        .find(|(offset, _span)| *offset < error.column)
+        .map(|(_offset, span)| *span)
+        .unwrap_or_else(Span::call_site);
+    let mut message = error.message.clone();
+    if let Some(class_name) = &error.class_name {
+        message = format!("in class `{}`: {}", class_name, message);
+    }
+    if let Some(suggestion) = &error.suggestion {
+        message = format!("{} ({})", message, suggestion);
+    }
+    let mut message = Literal::string(&message);
+    message.set_span(span);
+    let mut arguments = Group::new(Delimiter::Parenthesis, TokenTree::Literal(message).into());
+    arguments.set_span(span);
+    let punct = |character: char| {
+        let mut punct = Punct::new(character, Spacing::Alone);
+        punct.set_span(span);
+        TokenTree::Punct(punct)
+    };
+    vec![
+        TokenTree::Ident(Ident::new("compile_error", span)),
+        punct('!'),
+        TokenTree::Group(arguments),
+        punct(';'),
+    ]
+    .into_iter()
+    .collect()
+}
+
+/// Generates the module of an algebra given in the single argument syntax of the `codegen` binary
+#[proc_macro]
+pub fn geometric_algebra(input: TokenStream) -> TokenStream {
+    let mut config = String::new();
+    let mut token_offsets = Vec::new();
+    join_tokens(input, &mut config, &mut token_offsets);
+    let algebra_descriptor = match AlgebraDescriptor::parse_inline(&config) {
+        Ok(algebra_descriptor) => algebra_descriptor,
+        Err(errors) => return errors.iter().map(|error| compile_error(error, &token_offsets)).collect(),
+    };
+    let mut emitter = Emitter {
+        rust_collector: Some(Vec::new()),
+        glsl_collector: None,
+        trait_coverage: None,
+    };
+    codegen::generate(&algebra_descriptor, "geometric_algebra", &mut emitter).unwrap();
+    let module = String::from_utf8(emitter.rust_collector.unwrap()).unwrap();
+    // The preamble of the generated code only allows these lints in files of the `geometric_algebra` crate itself
+    format!(
+        "#[allow(clippy::assign_op_pattern, clippy::from_over_into)]\npub mod {} {{\n{}}}\n",
+        algebra_descriptor.name, module
+    )
+    .parse()
+    .unwrap()
+}
@@ -0,0 +1,30 @@
+use codegen_macro::geometric_algebra;
+use geometric_algebra::{CommutatorProduct, GeometricProduct};
+
+geometric_algebra! {
+    pga2d: 0, 1, 1;
+    Scalar: 1;
+    Point: e12, e01, -e02;
+    IdealPoint: e01, -e02;
+    Translator: 1, e01, -e02;
+    enable CommutatorProduct;
+    involution GradeInvolution: 1, 2;
+    product FatDot: t == |r-s|;
+    [Point]: GeometricProduct, CommutatorProduct, GradeInvolution, FatDot
+}
+
+use pga2d::{FatDot, GradeInvolution};
+
+#[test]
+fn descriptor_lines() {
+    let a = pga2d::Point::new(2.0, 3.0, 4.0);
+    let b = pga2d::Point::new(1.0, -1.0, 2.0);
+    assert_eq!(<[f32; 3]>::from(a.grade_involution()), [-2.0, -3.0, -4.0]);
+    assert_eq!(a.fat_dot(b), -2.0);
+    let ab = <[f32; 3]>::from(a.geometric_product(b));
+    let ba = <[f32; 3]>::from(b.geometric_product(a));
+    assert_eq!(ab[0], -2.0);
+    let commutator = <[f32; 2]>::from(a.commutator_product(b));
+    assert_ne!(commutator, [0.0, 0.0]);
+    assert_eq!(commutator, [(ab[1] - ba[1]) * 0.5, (ab[2] - ba[2]) * 0.5]);
+}
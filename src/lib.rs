@@ -1,3 +1,4 @@
+#![allow(clippy::macro_metavars_in_unsafe)]
 pub mod epga1d;
 pub mod ppga1d;
 pub mod hpga1d;
@@ -7,6 +8,9 @@ pub mod hpga2d;
 pub mod epga3d;
 pub mod ppga3d;
 pub mod hpga3d;
+pub mod cga2d;
+pub mod cga3d;
+pub mod sta;
 pub mod simd;
 pub mod polynomial;
 
@@ -94,6 +98,26 @@ impl ScalarProduct<f32> for f32 {
     }
 }
 
+impl AnticommutatorProduct<f32> for f32 {
+    type Output = f32;
+
+    fn anticommutator_product(self, other: f32) -> f32 {
+        self * other
+    }
+}
+
+impl GradeSelect for f32 {
+    type Output = f32;
+
+    fn grade_select(self, grade_mask: u128) -> f32 {
+        if grade_mask & 1 == 0 {
+            0.0
+        } else {
+            self
+        }
+    }
+}
+
 impl SquaredMagnitude for f32 {
     type Output = f32;
 
@@ -161,160 +185,133 @@ impl epga1d::ComplexNumber {
     }
 }
 
-impl Exp for epga1d::ComplexNumber {
-    type Output = Self;
-
-    fn exp(self) -> Self {
-        Self::from_polar(self[0].exp(), self[1])
+impl cga2d::DualCircle {
+    /// The null vector `no + x + x^2 / 2 * ni` of a Euclidean point
+    pub fn from_point(x: f32, y: f32) -> Self {
+        Self::new(x, y, 1.0, 0.5 * (x * x + y * y))
     }
-}
 
-impl Ln for epga1d::ComplexNumber {
-    type Output = Self;
+    pub fn from_center_and_radius(x: f32, y: f32, radius: f32) -> Self {
+        Self::new(x, y, 1.0, 0.5 * (x * x + y * y - radius * radius))
+    }
 
-    fn ln(self) -> Self {
-        Self::new(self.magnitude().ln(), self.arg())
+    /// The line with unit normal `(x, y)` at `distance` from the origin
+    pub fn from_normal_and_distance(x: f32, y: f32, distance: f32) -> Self {
+        Self::new(x, y, 0.0, distance)
     }
-}
 
-impl Powf for epga1d::ComplexNumber {
-    type Output = Self;
+    pub fn center(self) -> Self {
+        Self::from_point(self.e1() / self.no(), self.e2() / self.no())
+    }
 
-    fn powf(self, exponent: f32) -> Self {
-        Self::from_polar(self.magnitude().powf(exponent), self.arg() * exponent)
+    /// `NaN` for imaginary rounds
+    pub fn radius(self) -> f32 {
+        self.squared_magnitude().sqrt() / self.no().abs()
     }
 }
 
-impl Exp for ppga2d::IdealPoint {
-    type Output = ppga2d::Translator;
-
-    fn exp(self) -> ppga2d::Translator {
-        ppga2d::Translator::new(1.0, self[0], self[1])
+impl cga2d::Rotor {
+    /// Rotates counterclockwise by `angle` around the origin
+    pub fn from_angle(angle: f32) -> Self {
+        Self::new((0.5 * angle).cos(), -(0.5 * angle).sin())
     }
 }
 
-impl Ln for ppga2d::Translator {
-    type Output = ppga2d::IdealPoint;
-
-    fn ln(self) -> ppga2d::IdealPoint {
-        let result: ppga2d::IdealPoint = self.into();
-        result * (1.0 / self[0])
+impl cga2d::Translator {
+    pub fn from_displacement(x: f32, y: f32) -> Self {
+        Self::new(1.0, -0.5 * x, -0.5 * y)
     }
 }
 
-impl Powf for ppga2d::Translator {
-    type Output = Self;
-
-    fn powf(self, exponent: f32) -> Self {
-        (self.ln() * exponent).exp()
+impl cga2d::Dilator {
+    /// Scales uniformly by `scale` around the origin
+    pub fn from_scale(scale: f32) -> Self {
+        let a = 0.5 * scale.ln();
+        Self::new(a.cosh(), a.sinh())
     }
 }
 
-impl Exp for ppga2d::Point {
-    type Output = ppga2d::Motor;
-
-    fn exp(self) -> ppga2d::Motor {
-        let det = self[0] * self[0];
-        if det <= 0.0 {
-            return ppga2d::Motor::new(1.0, 0.0, self[1], self[2]);
-        }
-        let a = det.sqrt();
-        let c = a.cos();
-        let s = a.sin() / a;
-        let g0 = simd::Simd32x3::from(s) * self.group0();
-        ppga2d::Motor::new(c, g0[0], g0[1], g0[2])
+impl cga2d::Transversion {
+    /// Maps `x` to `(x + x^2 * b) / (1 + 2 * x.b + x^2 * b^2)`
+    pub fn from_vector(x: f32, y: f32) -> Self {
+        Self::new(1.0, -x, -y)
     }
 }
 
-impl Ln for ppga2d::Motor {
-    type Output = ppga2d::Point;
-
-    fn ln(self) -> ppga2d::Point {
-        let det = 1.0 - self[0] * self[0];
-        if det <= 0.0 {
-            return ppga2d::Point::new(0.0, self[2], self[3]);
-        }
-        let a = 1.0 / det;
-        let b = self[0].acos() * a.sqrt();
-        let g0 = simd::Simd32x4::from(b) * self.group0();
-        ppga2d::Point::new(g0[1], g0[2], g0[3])
+impl cga3d::DualSphere {
+    /// The null vector `no + x + x^2 / 2 * ni` of a Euclidean point
+    pub fn from_point(x: f32, y: f32, z: f32) -> Self {
+        Self::new(x, y, z, 1.0, 0.5 * (x * x + y * y + z * z))
     }
-}
 
-impl Powf for ppga2d::Motor {
-    type Output = Self;
+    pub fn from_center_and_radius(x: f32, y: f32, z: f32, radius: f32) -> Self {
+        Self::new(x, y, z, 1.0, 0.5 * (x * x + y * y + z * z - radius * radius))
+    }
 
-    fn powf(self, exponent: f32) -> Self {
-        (self.ln() * exponent).exp()
+    /// The plane with unit normal `(x, y, z)` at `distance` from the origin
+    pub fn from_normal_and_distance(x: f32, y: f32, z: f32, distance: f32) -> Self {
+        Self::new(x, y, z, 0.0, distance)
     }
-}
 
-impl Exp for ppga3d::IdealPoint {
-    type Output = ppga3d::Translator;
+    pub fn center(self) -> Self {
+        Self::from_point(self.e1() / self.no(), self.e2() / self.no(), self.e3() / self.no())
+    }
 
-    fn exp(self) -> ppga3d::Translator {
-        ppga3d::Translator::new(1.0, self[0], self[1], self[2])
+    /// `NaN` for imaginary rounds
+    pub fn radius(self) -> f32 {
+        self.squared_magnitude().sqrt() / self.no().abs()
     }
 }
 
-impl Ln for ppga3d::Translator {
-    type Output = ppga3d::IdealPoint;
-
-    fn ln(self) -> ppga3d::IdealPoint {
-        let result: ppga3d::IdealPoint = self.into();
-        result * (1.0 / self[0])
+impl cga3d::Rotor {
+    /// Rotates counterclockwise by `angle` around the unit axis `(x, y, z)` through the origin
+    pub fn from_axis_angle(x: f32, y: f32, z: f32, angle: f32) -> Self {
+        let s = (0.5 * angle).sin();
+        Self::new((0.5 * angle).cos(), -s * x, -s * y, -s * z)
     }
 }
 
-impl Powf for ppga3d::Translator {
-    type Output = Self;
-
-    fn powf(self, exponent: f32) -> Self {
-        (self.ln() * exponent).exp()
+impl cga3d::Translator {
+    pub fn from_displacement(x: f32, y: f32, z: f32) -> Self {
+        Self::new(1.0, -0.5 * x, -0.5 * y, -0.5 * z)
     }
 }
 
-impl Exp for ppga3d::Line {
-    type Output = ppga3d::Motor;
+impl cga3d::Dilator {
+    /// Scales uniformly by `scale` around the origin
+    pub fn from_scale(scale: f32) -> Self {
+        let a = 0.5 * scale.ln();
+        Self::new(a.cosh(), a.sinh())
+    }
+}
 
-    fn exp(self) -> ppga3d::Motor {
-        let det = self[3] * self[3] + self[4] * self[4] + self[5] * self[5];
-        if det <= 0.0 {
-            return ppga3d::Motor::new(1.0, 0.0, 0.0, 0.0, 0.0, self[0], self[1], self[2]);
-        }
-        let a = det.sqrt();
-        let c = a.cos();
-        let s = a.sin() / a;
-        let m = self[0] * self[3] + self[1] * self[4] + self[2] * self[5];
-        let t = m / det * (c - s);
-        let g0 = simd::Simd32x3::from(s) * self.group1();
-        let g1 = simd::Simd32x3::from(s) * self.group0() + simd::Simd32x3::from(t) * self.group1();
-        ppga3d::Motor::new(c, g0[0], g0[1], g0[2], s * m, g1[0], g1[1], g1[2])
+impl cga3d::Transversion {
+    /// Maps `x` to `(x + x^2 * b) / (1 + 2 * x.b + x^2 * b^2)`
+    pub fn from_vector(x: f32, y: f32, z: f32) -> Self {
+        Self::new(1.0, -x, -y, -z)
     }
 }
 
-impl Ln for ppga3d::Motor {
-    type Output = ppga3d::Line;
+impl sta::Spinor {
+    /// Boosts by `rapidity` in the direction of the unit vector `(x, y, z)`
+    pub fn from_rapidity(x: f32, y: f32, z: f32, rapidity: f32) -> Self {
+        let s = (0.5 * rapidity).sinh();
+        Self::new((0.5 * rapidity).cosh(), 0.0, 0.0, 0.0, 0.0, -s * x, -s * y, -s * z)
+    }
 
-    fn ln(self) -> ppga3d::Line {
-        let det = 1.0 - self[0] * self[0];
-        if det <= 0.0 {
-            return ppga3d::Line::new(self[5], self[6], self[7], 0.0, 0.0, 0.0);
+    /// Boosts a particle at rest to the velocity `(x, y, z)`, given in units of the speed of light
+    pub fn from_velocity(x: f32, y: f32, z: f32) -> Self {
+        let speed = (x * x + y * y + z * z).sqrt();
+        if speed <= 0.0 {
+            return Self::one();
         }
-        let a = 1.0 / det;
-        let b = self[0].acos() * a.sqrt();
-        let c = a * self[4] * (1.0 - self[0] * b);
-        let g0 = simd::Simd32x4::from(b) * self.group1() + simd::Simd32x4::from(c) * self.group0();
-        let g1 = simd::Simd32x4::from(b) * self.group0();
-        ppga3d::Line::new(g0[1], g0[2], g0[3], g1[1], g1[2], g1[3])
+        Self::from_rapidity(x / speed, y / speed, z / speed, speed.atanh())
     }
-}
 
-impl Powf for ppga3d::Motor {
-    type Output = Self;
-
-    fn powf(self, exponent: f32) -> Self {
-        (self.ln() * exponent).exp()
+    /// Rotates counterclockwise by `angle` around the unit axis `(x, y, z)`
+    pub fn from_axis_angle(x: f32, y: f32, z: f32, angle: f32) -> Self {
+        let s = (0.5 * angle).sin();
+        Self::new((0.5 * angle).cos(), s * x, s * y, s * z, 0.0, 0.0, 0.0, 0.0)
     }
 }
 
@@ -328,10 +325,34 @@ pub trait One {
     fn one() -> Self;
 }
 
-/// Element order reversed
-pub trait Dual {
+/// Basis elements replaced by their complement, signed such that `a ∧ a.right_complement()` is the pseudoscalar
+///
+/// Does not depend on the metric and is inverted by `left_complement`
+pub trait RightComplement {
     type Output;
-    fn dual(self) -> Self::Output;
+    fn right_complement(self) -> Self::Output;
+}
+
+/// Basis elements replaced by their complement, signed such that `a.left_complement() ∧ a` is the pseudoscalar
+///
+/// Differs from `right_complement` by `(-1)^(grade * (dimension - grade))`, so only in even dimensions
+pub trait LeftComplement {
+    type Output;
+    fn left_complement(self) -> Self::Output;
+}
+
+/// Right complement of the metric applied to `self`, such that `a ∧ b.hodge_dual()` is `a.scalar_product(b.reversal())` times the pseudoscalar
+///
+/// Elements containing a degenerate generator are mapped to zero. Also called bulk dual
+pub trait HodgeDual {
+    type Output;
+    fn hodge_dual(self) -> Self::Output;
+}
+
+/// Metric applied to the left complement of `self`, which inverts `hodge_dual` in non-degenerate metrics
+pub trait InverseHodgeDual {
+    type Output;
+    fn inverse_hodge_dual(self) -> Self::Output;
 }
 
 /// Negates elements with `grade % 2 == 1`
@@ -356,6 +377,14 @@ pub trait Conjugation {
     fn conjugation(self) -> Self::Output;
 }
 
+/// Negates elements with `(dimension - grade) % 4 >= 2`
+///
+/// Also called antireverse
+pub trait AntiReversal {
+    type Output;
+    fn anti_reversal(self) -> Self::Output;
+}
+
 /// General multi vector multiplication
 pub trait GeometricProduct<T> {
     type Output;
@@ -370,7 +399,7 @@ pub trait GeometricQuotient<T> {
 
 /// Dual of the geometric product grade filtered by `t == r + s`
 ///
-/// Also called join
+/// Also called join or antiwedge product
 pub trait RegressiveProduct<T> {
     type Output;
     fn regressive_product(self, other: T) -> Self::Output;
@@ -410,6 +439,36 @@ pub trait ScalarProduct<T> {
     fn scalar_product(self, other: T) -> Self::Output;
 }
 
+/// `(a * b - b * a) / 2`, which keeps the anticommuting part of the geometric product
+///
+/// Also called Lie bracket
+pub trait CommutatorProduct<T> {
+    type Output;
+    fn commutator_product(self, other: T) -> Self::Output;
+}
+
+/// `(a * b + b * a) / 2`, which keeps the commuting part of the geometric product
+pub trait AnticommutatorProduct<T> {
+    type Output;
+    fn anticommutator_product(self, other: T) -> Self::Output;
+}
+
+/// Dual of the geometric product of the duals
+///
+/// Also called geometric antiproduct
+pub trait GeometricAntiProduct<T> {
+    type Output;
+    fn geometric_anti_product(self, other: T) -> Self::Output;
+}
+
+/// Dual of the geometric product of the duals grade filtered by `t == dimension`, with `other.anti_reversal()`
+///
+/// Also called antidot product
+pub trait AntiDotProduct<T> {
+    type Output;
+    fn anti_dot_product(self, other: T) -> Self::Output;
+}
+
 /// `self * other * self.reversion()`
 ///
 /// Also called sandwich product
@@ -418,6 +477,38 @@ pub trait Transformation<T> {
     fn transformation(self, other: T) -> Self::Output;
 }
 
+/// `self * other * self.anti_reversal()` using the geometric antiproduct
+///
+/// Also called antisandwich product
+pub trait AntiTransformation<T> {
+    type Output;
+    fn anti_transformation(self, other: T) -> Self::Output;
+}
+
+/// `self.inner_product(other) * other.inverse()`
+///
+/// Orthogonal projection onto `other`, e.g. the point on a plane closest to a point
+pub trait ProjectOnto<T> {
+    type Output;
+    fn project_onto(self, other: T) -> Self::Output;
+}
+
+/// `self.outer_product(other) * other.inverse()`
+///
+/// Also called orthogonal rejection, the part of `self` which is orthogonal to `other`
+pub trait RejectFrom<T> {
+    type Output;
+    fn reject_from(self, other: T) -> Self::Output;
+}
+
+/// `other * self * other.inverse()`, with `self.automorphism()` if `other` is an odd versor
+///
+/// E.g. the mirror image in a plane or the half turn about a line
+pub trait ReflectIn<T> {
+    type Output;
+    fn reflect_in(self, other: T) -> Self::Output;
+}
+
 /// Square of the magnitude
 pub trait SquaredMagnitude {
     type Output;
@@ -441,23 +532,112 @@ pub trait Signum {
 }
 
 /// Raises a number to the scalar power of `-1.0`
+///
+/// Elements which have no inverse, like null vectors, result in non-finite values.
+/// Use `try_inverse()` to detect them.
 pub trait Inverse {
     type Output;
     fn inverse(self) -> Self::Output;
 }
 
+/// Raises a number to the scalar power of `-1.0` or returns `None` if it has no inverse
+///
+/// The inverse scales all elements by one over a determinant, so either all of them are finite or none.
+pub trait TryInverse {
+    type Output;
+    fn try_inverse(self) -> Option<Self::Output>;
+}
+
+impl<T: Inverse> TryInverse for T
+where
+    T::Output: std::ops::Index<usize, Output = f32>,
+{
+    type Output = T::Output;
+
+    fn try_inverse(self) -> Option<T::Output> {
+        Some(self.inverse()).filter(|inverse| inverse[0].is_finite())
+    }
+}
+
+/// Magnitude of the elements which contain the degenerate generator, as antiscalar
+///
+/// Unlike `magnitude()` it does not vanish for ideal elements, e.g. directions
+pub trait BulkNorm {
+    type Output;
+    fn bulk_norm(self) -> Self::Output;
+}
+
+/// Magnitude of the elements which do not contain the degenerate generator, as scalar
+pub trait WeightNorm {
+    type Output;
+    fn weight_norm(self) -> Self::Output;
+}
+
+/// Bulk norm divided by weight norm, as scalar
+///
+/// For a unitized point, line or plane this is its distance to the origin
+pub trait GeometricNorm {
+    type Output;
+    fn geometric_norm(self) -> Self::Output;
+}
+
+/// Scales to a weight norm of `1.0`
+///
+/// Also called normalize in projective geometric algebras
+pub trait Unitize {
+    type Output;
+    fn unitize(self) -> Self::Output;
+}
+
 /// The natural logarithm
+///
+/// Maps an even versor back to the bivector generating it, picking one of them if there are several, like for a double rotation
 pub trait Ln {
     type Output;
     fn ln(self) -> Self::Output;
 }
 
 /// The exponential function
+///
+/// Maps a bivector to the even versor it generates, e.g. a line to a motor
 pub trait Exp {
     type Output;
     fn exp(self) -> Self::Output;
 }
 
+/// Zeroes the elements whose grade `k` is not set in the bit mask, e.g. `grade_mask = 0b101` keeps grades 0 and 2
+pub trait GradeSelect {
+    type Output;
+    fn grade_select(self, grade_mask: u128) -> Self::Output;
+}
+
+macro_rules! grade_projections {
+    ($($trait_name:ident $method_name:ident $grade:literal,)*) => {
+        $(
+            #[doc = concat!("Projection onto the elements of grade ", $grade)]
+            pub trait $trait_name {
+                type Output;
+                fn $method_name(self) -> Self::Output;
+            }
+        )*
+    };
+}
+
+// One for each grade of the largest algebras, which have 64 generators
+grade_projections!(
+    Grade0 grade_0 0, Grade1 grade_1 1, Grade2 grade_2 2, Grade3 grade_3 3, Grade4 grade_4 4, Grade5 grade_5 5, Grade6 grade_6 6, Grade7 grade_7 7,
+    Grade8 grade_8 8, Grade9 grade_9 9, Grade10 grade_10 10, Grade11 grade_11 11, Grade12 grade_12 12, Grade13 grade_13 13, Grade14 grade_14 14,
+    Grade15 grade_15 15, Grade16 grade_16 16, Grade17 grade_17 17, Grade18 grade_18 18, Grade19 grade_19 19, Grade20 grade_20 20,
+    Grade21 grade_21 21, Grade22 grade_22 22, Grade23 grade_23 23, Grade24 grade_24 24, Grade25 grade_25 25, Grade26 grade_26 26,
+    Grade27 grade_27 27, Grade28 grade_28 28, Grade29 grade_29 29, Grade30 grade_30 30, Grade31 grade_31 31, Grade32 grade_32 32,
+    Grade33 grade_33 33, Grade34 grade_34 34, Grade35 grade_35 35, Grade36 grade_36 36, Grade37 grade_37 37, Grade38 grade_38 38,
+    Grade39 grade_39 39, Grade40 grade_40 40, Grade41 grade_41 41, Grade42 grade_42 42, Grade43 grade_43 43, Grade44 grade_44 44,
+    Grade45 grade_45 45, Grade46 grade_46 46, Grade47 grade_47 47, Grade48 grade_48 48, Grade49 grade_49 49, Grade50 grade_50 50,
+    Grade51 grade_51 51, Grade52 grade_52 52, Grade53 grade_53 53, Grade54 grade_54 54, Grade55 grade_55 55, Grade56 grade_56 56,
+    Grade57 grade_57 57, Grade58 grade_58 58, Grade59 grade_59 59, Grade60 grade_60 60, Grade61 grade_61 61, Grade62 grade_62 62,
+    Grade63 grade_63 63, Grade64 grade_64 64,
+);
+
 /// Raises a number to an integer scalar power
 pub trait Powi {
     type Output;
@@ -469,3 +649,11 @@ pub trait Powf {
     type Output;
     fn powf(self, exponent: f32) -> Self::Output;
 }
+
+/// Square root of an even versor, e.g. the rotation or motion halfway to the given one
+///
+/// The rotation by a full turn and other versors without a unique square root result in non-finite values.
+pub trait Sqrt {
+    type Output;
+    fn sqrt(self) -> Self::Output;
+}
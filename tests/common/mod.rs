@@ -0,0 +1,23 @@
+pub fn assert_close(actual: &[f32], expected: &[f32]) {
+    for (actual, expected) in actual.iter().zip(expected) {
+        assert!((actual - expected).abs() < 1.0e-4, "{:?} != {:?}", actual, expected);
+    }
+}
+
+/// Some multivector whose elements are all different
+#[allow(dead_code)]
+pub fn elements<const N: usize>() -> [f32; N] {
+    let mut elements = [0.0; N];
+    for (i, element) in elements.iter_mut().enumerate() {
+        *element = ((i * 7 + 3) % 11) as f32 / 11.0 - 0.4;
+    }
+    elements
+}
+
+/// The scalar one followed by `N - 1` zeros
+#[allow(dead_code)]
+pub fn one<const N: usize>() -> [f32; N] {
+    let mut one = [0.0; N];
+    one[0] = 1.0;
+    one
+}
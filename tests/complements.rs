@@ -0,0 +1,33 @@
+mod common;
+
+use common::*;
+use geometric_algebra::*;
+
+#[test]
+fn left_complement_of_right_complement() {
+    let x = ppga3d::MultiVector::from(elements::<16>());
+    assert_close(&<[f32; 16]>::from(x.right_complement().left_complement()), &elements::<16>());
+    let x = epga3d::MultiVector::from(elements::<16>());
+    assert_close(&<[f32; 16]>::from(x.right_complement().left_complement()), &elements::<16>());
+    let x = sta::MultiVector::from(elements::<16>());
+    assert_close(&<[f32; 16]>::from(x.right_complement().left_complement()), &elements::<16>());
+    let x = cga3d::MultiVector::from(elements::<32>());
+    assert_close(&<[f32; 32]>::from(x.right_complement().left_complement()), &elements::<32>());
+}
+
+#[test]
+fn inverse_hodge_dual_of_hodge_dual() {
+    let x = epga3d::MultiVector::from(elements::<16>());
+    assert_close(&<[f32; 16]>::from(x.hodge_dual().inverse_hodge_dual()), &elements::<16>());
+    let x = sta::MultiVector::from(elements::<16>());
+    assert_close(&<[f32; 16]>::from(x.hodge_dual().inverse_hodge_dual()), &elements::<16>());
+    let x = cga3d::MultiVector::from(elements::<32>());
+    assert_close(&<[f32; 32]>::from(x.hodge_dual().inverse_hodge_dual()), &elements::<32>());
+}
+
+#[test]
+fn plane_wedge_right_complement() {
+    let plane = ppga3d::Plane::new(0.0, 1.0, 0.0, 0.0);
+    assert_close(&<[f32; 1]>::from(plane.outer_product(plane.right_complement())), &[1.0]);
+    assert_close(&<[f32; 1]>::from(plane.left_complement().outer_product(plane)), &[1.0]);
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use common::*;
+use geometric_algebra::*;
+
+#[test]
+fn complex_number_functions() {
+    let z = epga1d::ComplexNumber::new(-1.0, 2.0);
+    let exponential = z.exp();
+    let expected = epga1d::ComplexNumber::from_polar((-1.0f32).exp(), 2.0);
+    assert_close(&<[f32; 2]>::from(exponential), &<[f32; 2]>::from(expected));
+    let logarithm = z.ln();
+    assert_close(&<[f32; 2]>::from(logarithm), &[5.0f32.sqrt().ln(), z.arg()]);
+    let square = epga1d::ComplexNumber::new(0.0, 2.0).powf(2.0);
+    assert_close(&<[f32; 2]>::from(square), &[-4.0, 0.0]);
+}
+
+#[test]
+fn dual_and_split_complex_number_logarithms() {
+    let dual = ppga1d::DualNumber::new(2.0, 3.0);
+    assert_close(&<[f32; 2]>::from(dual.ln()), &[2.0f32.ln(), 1.5]);
+    let split = hpga1d::SplitComplexNumber::new(2.0, 1.0);
+    assert_close(&<[f32; 2]>::from(split.ln()), &[3.0f32.sqrt().ln(), 0.5f32.atanh()]);
+    assert_close(&<[f32; 2]>::from(split.ln().exp()), &[2.0, 1.0]);
+}
+
+#[test]
+fn logarithm_of_exponential() {
+    let line = ppga3d::Line::new(0.3, -0.2, 0.5, 0.4, 0.1, -0.7);
+    assert_close(&<[f32; 6]>::from(line.exp().ln()), &<[f32; 6]>::from(line));
+    let line = epga3d::Line::new(0.3, -0.2, 0.5, 0.4, 0.1, -0.7);
+    assert_close(&<[f32; 6]>::from(line.exp().ln()), &<[f32; 6]>::from(line));
+    let bivector = sta::Bivector::new(0.3, -0.2, 0.5, 0.4, 0.1, -0.7);
+    assert_close(&<[f32; 6]>::from(bivector.exp().ln()), &<[f32; 6]>::from(bivector));
+    let point_pair = cga3d::PointPair::new(0.4, 0.1, -0.7, 0.2, 0.3, -0.2, 0.5, -0.1, 0.6, 0.2);
+    assert_close(&<[f32; 10]>::from(point_pair.exp().ln()), &<[f32; 10]>::from(point_pair));
+    let rotor = ppga3d::Rotor::new(0.0, 0.4, 0.1, -0.7);
+    assert_close(&<[f32; 4]>::from(rotor.exp().ln()), &<[f32; 4]>::from(rotor));
+}
+
+#[test]
+fn square_of_square_root() {
+    let motor = ppga3d::Line::new(0.3, -0.2, 0.5, 0.4, 0.1, -0.7).exp().geometric_product(2.0);
+    let root = motor.sqrt();
+    assert_close(&<[f32; 8]>::from(root.geometric_product(root)), &<[f32; 8]>::from(motor));
+    let motor = epga3d::Line::new(0.3, -0.2, 0.5, 0.4, 0.1, -0.7).exp().geometric_product(2.0);
+    let root = motor.sqrt();
+    assert_close(&<[f32; 8]>::from(root.geometric_product(root)), &<[f32; 8]>::from(motor));
+    let spinor = sta::Bivector::new(0.3, -0.2, 0.5, 0.4, 0.1, -0.7).exp().geometric_product(2.0);
+    let root = spinor.sqrt();
+    assert_close(&<[f32; 8]>::from(root.geometric_product(root)), &<[f32; 8]>::from(spinor));
+    let rotor = cga3d::Rotor::from_axis_angle(0.0, 0.6, 0.8, 2.0);
+    let motor = rotor.geometric_product(cga3d::Translator::from_displacement(1.0, -2.0, 3.0));
+    let root = motor.sqrt();
+    assert_close(&<[f32; 8]>::from(root.geometric_product(root)), &<[f32; 8]>::from(motor));
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use common::*;
+use geometric_algebra::*;
+
+#[test]
+fn multi_vector_times_inverse() {
+    let x = ppga3d::MultiVector::from(elements::<16>());
+    assert_close(&<[f32; 16]>::from(x.geometric_product(x.inverse())), &one::<16>());
+    let x = epga3d::MultiVector::from(elements::<16>());
+    assert_close(&<[f32; 16]>::from(x.geometric_product(x.inverse())), &one::<16>());
+    let x = sta::MultiVector::from(elements::<16>());
+    assert_close(&<[f32; 16]>::from(x.geometric_product(x.inverse())), &one::<16>());
+    let x = cga3d::MultiVector::from(elements::<32>());
+    assert_close(&<[f32; 32]>::from(x.geometric_product(x.inverse())), &one::<32>());
+}
+
+#[test]
+fn null_vectors_have_no_inverse() {
+    assert!(ppga3d::Plane::new(1.0, 0.0, 0.0, 0.0).try_inverse().is_none());
+    assert!(ppga3d::Line::new(1.0, 2.0, 3.0, 0.0, 0.0, 0.0).try_inverse().is_none());
+    assert!(cga3d::DualSphere::from_point(1.0, -2.0, 3.0).try_inverse().is_none());
+    assert!(sta::Vector::new(5.0, 0.0, 3.0, 4.0).try_inverse().is_none());
+    assert!(sta::Vector::new(5.0, 0.0, 3.0, 3.0).try_inverse().is_some());
+    assert!(ppga3d::Plane::new(1.0, 2.0, 3.0, 4.0).try_inverse().is_some());
+}
+
+/// A motor times its reversal also has a pseudoscalar part unless it satisfies the Study condition,
+/// so the reversal divided by the squared magnitude is no inverse of it.
+#[test]
+fn motors_outside_the_study_quadric() {
+    let motor = ppga3d::Motor::from(elements::<8>());
+    assert_close(&<[f32; 8]>::from(motor.geometric_product(motor.inverse())), &one::<8>());
+    let line = ppga3d::Line::new(0.3, -0.2, 0.5, 0.4, 0.1, -0.7);
+    assert_close(&<[f32; 8]>::from(line.geometric_product(line.inverse())), &one::<8>());
+}
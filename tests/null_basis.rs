@@ -0,0 +1,26 @@
+mod common;
+
+use common::*;
+use geometric_algebra::*;
+
+#[test]
+fn origin_times_infinity() {
+    let origin = cga3d::DualSphere::new(0.0, 0.0, 0.0, 1.0, 0.0);
+    let infinity = cga3d::DualSphere::new(0.0, 0.0, 0.0, 0.0, 1.0);
+    // no ni = no · ni + no ∧ ni
+    assert_close(&[origin.inner_product(infinity)], &[-1.0]);
+    let mut expected = [0.0; 10];
+    expected[3] = 1.0;
+    assert_close(&<[f32; 10]>::from(origin.outer_product(infinity)), &expected);
+    assert_close(&[origin.inner_product(origin), infinity.inner_product(infinity)], &[0.0, 0.0]);
+}
+
+#[test]
+fn inner_product_of_points() {
+    let a = cga3d::DualSphere::from_point(1.0, 2.0, 3.0);
+    let b = cga3d::DualSphere::from_point(4.0, 6.0, 3.0);
+    assert_close(&[a.scalar_product(a), a.scalar_product(b)], &[0.0, -12.5]);
+    let a = cga2d::DualCircle::from_point(1.0, 2.0);
+    let b = cga2d::DualCircle::from_point(4.0, 6.0);
+    assert_close(&[a.scalar_product(a), a.scalar_product(b)], &[0.0, -12.5]);
+}
@@ -0,0 +1,14 @@
+mod common;
+
+use common::*;
+use geometric_algebra::*;
+
+#[test]
+fn reject_point_from_plane() {
+    let point = ppga3d::Point::new(1.0, 1.0, 2.0, 3.0);
+    let plane = ppga3d::Plane::new(0.0, 0.0, 0.0, 1.0);
+    let projection = point.project_onto(plane);
+    let rejection = point.reject_from(plane);
+    assert_close(&<[f32; 4]>::from(projection), &[1.0, 1.0, 2.0, 0.0]);
+    assert_close(&<[f32; 4]>::from(rejection), &[0.0, 0.0, 0.0, 3.0]);
+}
@@ -0,0 +1,29 @@
+mod common;
+
+use common::*;
+use geometric_algebra::*;
+
+#[test]
+fn translate_dual_sphere() {
+    let sphere = cga3d::DualSphere::from_center_and_radius(1.0, 2.0, 3.0, 2.0);
+    let translated = cga3d::Translator::from_displacement(4.0, -5.0, 6.0).transformation(sphere);
+    let expected = cga3d::DualSphere::from_center_and_radius(5.0, -3.0, 9.0, 2.0);
+    assert_close(&<[f32; 5]>::from(translated), &<[f32; 5]>::from(expected));
+    assert_close(&[translated.radius()], &[2.0]);
+}
+
+#[test]
+fn translate_dual_circle() {
+    let circle = cga2d::DualCircle::from_center_and_radius(1.0, 2.0, 3.0);
+    let translated = cga2d::Translator::from_displacement(-4.0, 5.0).transformation(circle);
+    let expected = cga2d::DualCircle::from_center_and_radius(-3.0, 7.0, 3.0);
+    assert_close(&<[f32; 4]>::from(translated), &<[f32; 4]>::from(expected));
+}
+
+#[test]
+fn rotate_dual_sphere() {
+    let sphere = cga3d::DualSphere::from_center_and_radius(1.0, 0.0, 0.0, 0.5);
+    let rotated = cga3d::Rotor::from_axis_angle(0.0, 0.0, 1.0, std::f32::consts::FRAC_PI_2).transformation(sphere);
+    let center = rotated.center();
+    assert_close(&[center.e1(), center.e2(), center.e3(), rotated.radius()], &[0.0, 1.0, 0.0, 0.5]);
+}